@@ -0,0 +1,5 @@
+-- Creation time used for sorting the todos list
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+-- Full-text index used by the todos list search
+CREATE INDEX IF NOT EXISTS todos_text_search_idx ON todos USING GIN (to_tsvector('simple', text));
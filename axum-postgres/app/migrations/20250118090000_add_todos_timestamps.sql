@@ -0,0 +1,10 @@
+ALTER TABLE todos
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN completed_at TIMESTAMPTZ;
+
+-- The real times are unknown for existing rows, creation is the closest lower bound
+UPDATE todos SET updated_at = created_at, completed_at = CASE WHEN completed THEN created_at END;
+
+-- Keyset pagination of the list sorted by time
+CREATE INDEX todos_created_at_idx ON todos (created_at, id);
+CREATE INDEX todos_updated_at_idx ON todos (updated_at, id);
@@ -0,0 +1,2 @@
+-- Incremented by every update, exposed as the ETag of a todo
+ALTER TABLE todos ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
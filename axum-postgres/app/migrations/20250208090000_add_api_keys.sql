@@ -0,0 +1,14 @@
+-- API keys of service clients, they authenticate as their owner with the role of the key.
+-- Only the SHA-256 hash of a key is stored, the key itself is shown once when created.
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    role TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX api_keys_owner_idx ON api_keys (owner, created_at, id);
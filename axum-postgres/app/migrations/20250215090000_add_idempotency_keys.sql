@@ -0,0 +1,15 @@
+-- Idempotency-Key headers of requests with the response to replay when a request is retried.
+-- A key without a response belongs to a request in progress, it expires soon so the key is
+-- released if the request is cancelled.
+CREATE TABLE idempotency_keys (
+    owner TEXT NOT NULL,
+    key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    response_status INTEGER,
+    response_body TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (owner, key)
+);
+
+CREATE INDEX idempotency_keys_expires_at_idx ON idempotency_keys (expires_at);
@@ -0,0 +1,15 @@
+-- Lists group the todos of an owner. Todos without a list, e.g. the existing ones, are only
+-- listed with all todos. Deleting a list deletes its todos unless they are moved first.
+CREATE TABLE lists (
+    id UUID PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX lists_owner_idx ON lists (owner, created_at, id);
+
+ALTER TABLE todos ADD COLUMN list_id UUID REFERENCES lists (id) ON DELETE CASCADE;
+
+CREATE INDEX todos_list_id_idx ON todos (list_id, id);
@@ -0,0 +1,16 @@
+-- Tags label the todos of an owner, a tag is shared by all the todos of the owner with it.
+-- Tags are kept when their last todo is removed.
+CREATE TABLE tags (
+    id BIGSERIAL PRIMARY KEY,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    UNIQUE (owner, name)
+);
+
+CREATE TABLE todo_tags (
+    todo_id UUID NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (todo_id, tag_id)
+);
+
+CREATE INDEX todo_tags_tag_id_idx ON todo_tags (tag_id, todo_id);
@@ -0,0 +1,9 @@
+-- Optional due date and a priority from 0 (low) to 2 (high), existing todos have the
+-- default medium priority
+ALTER TABLE todos
+    ADD COLUMN due_at TIMESTAMPTZ,
+    ADD COLUMN priority SMALLINT NOT NULL DEFAULT 1 CHECK (priority BETWEEN 0 AND 2);
+
+-- Keyset pagination of the list sorted by due date, todos without one are sorted last
+CREATE INDEX todos_due_at_idx ON todos ((COALESCE(due_at, '9999-12-31T23:59:59Z')), id);
+CREATE INDEX todos_priority_idx ON todos (priority, id);
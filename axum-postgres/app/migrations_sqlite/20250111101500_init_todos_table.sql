@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS todos (
+    id BLOB PRIMARY KEY,
+    text TEXT NOT NULL,
+    completed BOOLEAN NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Full-text index used by the todos list search, tokenized like Postgres 'simple' config
+CREATE VIRTUAL TABLE IF NOT EXISTS todos_search USING fts5(
+    text,
+    content = 'todos',
+    content_rowid = 'rowid',
+    tokenize = 'unicode61 remove_diacritics 0'
+);
+
+CREATE TRIGGER IF NOT EXISTS todos_search_insert AFTER INSERT ON todos BEGIN
+    INSERT INTO todos_search (rowid, text) VALUES (new.rowid, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS todos_search_delete AFTER DELETE ON todos BEGIN
+    INSERT INTO todos_search (todos_search, rowid, text) VALUES ('delete', old.rowid, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS todos_search_update AFTER UPDATE OF text ON todos BEGIN
+    INSERT INTO todos_search (todos_search, rowid, text) VALUES ('delete', old.rowid, old.text);
+    INSERT INTO todos_search (rowid, text) VALUES (new.rowid, new.text);
+END;
@@ -0,0 +1,5 @@
+-- User who created the todo, todos are only visible to their owner. Existing todos have
+-- no owner and are not visible to any user until it is set.
+ALTER TABLE todos ADD COLUMN owner TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX todos_owner_idx ON todos (owner, id);
@@ -0,0 +1,58 @@
+-- The full-text index refers to the todos by rowid, which VACUUM may renumber in tables without
+-- an INTEGER PRIMARY KEY. The table is rebuilt with `seq` as a stable rowid. Dropping the old
+-- table deletes the tags of the todos, so they are copied and restored after the rebuild.
+CREATE TEMP TABLE todo_tags_copy AS SELECT todo_id, tag_id FROM todo_tags;
+
+CREATE TABLE todos_new (
+    seq INTEGER PRIMARY KEY,
+    id BLOB NOT NULL UNIQUE,
+    text TEXT NOT NULL,
+    completed BOOLEAN NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT '',
+    completed_at TEXT,
+    version INTEGER NOT NULL DEFAULT 1,
+    owner TEXT NOT NULL DEFAULT '',
+    list_id BLOB REFERENCES lists (id) ON DELETE CASCADE,
+    due_at TEXT,
+    priority INTEGER NOT NULL DEFAULT 1 CHECK (priority BETWEEN 0 AND 2)
+);
+
+INSERT INTO todos_new (id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority)
+SELECT id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority
+FROM todos ORDER BY rowid;
+
+DROP TABLE todos_search;
+DROP TABLE todos;
+ALTER TABLE todos_new RENAME TO todos;
+
+INSERT INTO todo_tags (todo_id, tag_id) SELECT todo_id, tag_id FROM todo_tags_copy;
+DROP TABLE todo_tags_copy;
+
+CREATE INDEX todos_created_at_idx ON todos (created_at, id);
+CREATE INDEX todos_updated_at_idx ON todos (updated_at, id);
+CREATE INDEX todos_owner_idx ON todos (owner, id);
+CREATE INDEX todos_list_id_idx ON todos (list_id, id);
+CREATE INDEX todos_due_at_idx ON todos (COALESCE(due_at, '9999-12-31T23:59:59+00:00'), id);
+CREATE INDEX todos_priority_idx ON todos (priority, id);
+
+CREATE VIRTUAL TABLE todos_search USING fts5(
+    text,
+    content = 'todos',
+    content_rowid = 'seq',
+    tokenize = 'unicode61 remove_diacritics 0'
+);
+INSERT INTO todos_search (todos_search) VALUES ('rebuild');
+
+CREATE TRIGGER todos_search_insert AFTER INSERT ON todos BEGIN
+    INSERT INTO todos_search (rowid, text) VALUES (new.seq, new.text);
+END;
+
+CREATE TRIGGER todos_search_delete AFTER DELETE ON todos BEGIN
+    INSERT INTO todos_search (todos_search, rowid, text) VALUES ('delete', old.seq, old.text);
+END;
+
+CREATE TRIGGER todos_search_update AFTER UPDATE OF text ON todos BEGIN
+    INSERT INTO todos_search (todos_search, rowid, text) VALUES ('delete', old.seq, old.text);
+    INSERT INTO todos_search (rowid, text) VALUES (new.seq, new.text);
+END;
@@ -1,101 +1,504 @@
-use std::env;
+use axum_postgres::datasources::database::redact_url;
+use axum_postgres::server::credentials::Credentials;
+use axum_postgres::server::rate_limit::{Quota, RateLimits};
+use axum_postgres::server::tokens::MIN_SECRET_LENGTH;
+use std::{collections::HashSet, env, fmt, fs, str::FromStr, time::Duration};
+use toml::{Table, Value};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Environment {
     Local,
     Production,
 }
 
-impl Environment {
-    fn from_str(env: &str) -> Self {
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(env: &str) -> Result<Self, Self::Err> {
         match env {
-            "production" => Environment::Production,
-            _ => Environment::Local,
+            "local" => Ok(Environment::Local),
+            "production" => Ok(Environment::Production),
+            _ => Err("must be local or production".to_string()),
         }
     }
+}
 
+impl Environment {
     pub fn is_local(&self) -> bool {
         matches!(self, Environment::Local)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err("must be trace, debug, info, warn or error".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        };
+        f.write_str(level)
+    }
+}
+
 pub struct Config {
     pub database_url: Option<String>,
     pub database_max_connections: u32,
-    pub port: String,
-    pub log_level: String,
-    pub credentials: Vec<(String, String)>,
+    pub database_run_migrations: bool,
+    pub port: u16,
+    pub metrics_port: u16,
+    pub log_level: LogLevel,
+    pub credentials: Credentials,
+    pub jwt_secret: Option<String>,
+    pub jwt_expiration: Duration,
+    pub rate_limits: RateLimits,
+    pub idempotency_ttl: Duration,
+    pub shutdown_drain: Duration,
+    pub otlp_endpoint: Option<String>,
+    pub otel_service_name: String,
     pub environment: Environment,
 }
 
 impl Config {
-    pub fn new() -> Self {
-        Config {
-            credentials: env::var("CREDENTIALS")
-                .unwrap_or_else(|_| "".to_string())
-                .split(',')
-                .filter_map(|cred| {
-                    let mut parts = cred.split(':');
-                    if let (Some(user), Some(pass)) = (parts.next(), parts.next()) {
-                        Some((user.to_string(), pass.to_string()))
-                    } else {
-                        None
-                    }
+    /// Loads the TOML file at `CONFIG_FILE`, if set, with env vars overriding its settings.
+    /// Fails with all invalid settings, not only the first one.
+    pub fn load() -> Result<Self, Vec<String>> {
+        let file = match env::var("CONFIG_FILE") {
+            Ok(path) => read_config_file(&path).map_err(|e| vec![e])?,
+            Err(_) => Table::new(),
+        };
+        Config::from_sources(file, |name| env::var(name).ok())
+    }
+
+    fn from_sources(
+        file: Table,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, Vec<String>> {
+        let mut settings = Settings {
+            file,
+            env,
+            read: HashSet::new(),
+            errors: Vec::new(),
+        };
+
+        let credentials = load_credentials(
+            settings.get("CREDENTIALS"),
+            settings.get("CREDENTIALS_FILE"),
+        )
+        .unwrap_or_else(|e| {
+            settings.errors.push(e);
+            Credentials::default()
+        });
+        let config = Config {
+            credentials,
+            jwt_secret: settings.parse("JWT_SECRET", |secret| {
+                if secret.len() < MIN_SECRET_LENGTH {
+                    return Err(format!("must have at least {} bytes", MIN_SECRET_LENGTH));
+                }
+                Ok(secret.to_string())
+            }),
+            jwt_expiration: settings
+                .seconds("JWT_EXPIRATION_SECONDS")
+                .unwrap_or(Duration::from_secs(3600)),
+            rate_limits: load_rate_limits(&mut settings),
+            idempotency_ttl: settings
+                .seconds("IDEMPOTENCY_TTL_SECONDS")
+                .unwrap_or(Duration::from_secs(86400)),
+            shutdown_drain: settings
+                .seconds("SHUTDOWN_DRAIN_SECONDS")
+                .unwrap_or(Duration::from_secs(5)),
+            database_url: settings.get("DATABASE_URL"),
+            database_max_connections: settings
+                .parse("DATABASE_MAX_CONNECTIONS", |value| match value.parse() {
+                    Ok(0) | Err(_) => Err(format!("must be a number above zero: {}", value)),
+                    Ok(max_connections) => Ok(max_connections),
                 })
-                .collect(),
-            database_url: env::var("DATABASE_URL").ok(),
-            database_max_connections: env::var("DATABASE_MAX_CONNECTIONS")
-                .unwrap_or_else(|_| "5".to_string())
+                .unwrap_or(5),
+            database_run_migrations: settings.get("DATABASE_RUN_MIGRATIONS").unwrap_or(true),
+            otlp_endpoint: settings.get("OTEL_EXPORTER_OTLP_ENDPOINT"),
+            otel_service_name: settings
+                .get("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|| env!("CARGO_PKG_NAME").to_string()),
+            environment: settings.get("ENVIRONMENT").unwrap_or(Environment::Local),
+            log_level: settings.get("LOG_LEVEL").unwrap_or(LogLevel::Debug),
+            port: settings.get("PORT").unwrap_or(3000),
+            metrics_port: settings.get("METRICS_PORT").unwrap_or(3001),
+        };
+        if config.port == config.metrics_port {
+            settings
+                .errors
+                .push("PORT and METRICS_PORT must be different".to_string());
+        }
+        settings.check_unknown_keys();
+
+        if settings.errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(settings.errors)
+        }
+    }
+}
+
+// logged at startup, so the secrets are left out
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field(
+                "database_url",
+                &self.database_url.as_deref().map(redact_url),
+            )
+            .field("database_max_connections", &self.database_max_connections)
+            .field("database_run_migrations", &self.database_run_migrations)
+            .field("port", &self.port)
+            .field("metrics_port", &self.metrics_port)
+            .field("log_level", &self.log_level)
+            .field("credentials", &self.credentials)
+            .field(
+                "jwt_secret",
+                &self.jwt_secret.as_ref().map(|_| "[redacted]"),
+            )
+            .field("jwt_expiration", &self.jwt_expiration)
+            .field("rate_limits", &self.rate_limits)
+            .field("idempotency_ttl", &self.idempotency_ttl)
+            .field("shutdown_drain", &self.shutdown_drain)
+            .field("otlp_endpoint", &self.otlp_endpoint)
+            .field("otel_service_name", &self.otel_service_name)
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+fn read_config_file(path: &str) -> Result<Table, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read CONFIG_FILE {}: {}", path, e))?;
+    content
+        .parse()
+        .map_err(|e| format!("CONFIG_FILE {} is not valid TOML: {}", path, e))
+}
+
+/// Settings of the config file, overridden by the env vars. The key of a setting in the file is
+/// the name of its env var in lowercase, e.g. `database_url` for `DATABASE_URL`. Invalid values
+/// are collected instead of failing on the first one.
+struct Settings<E> {
+    file: Table,
+    env: E,
+    read: HashSet<String>,
+    errors: Vec<String>,
+}
+
+impl<E: Fn(&str) -> Option<String>> Settings<E> {
+    fn value(&mut self, name: &str) -> Option<String> {
+        let key = name.to_lowercase();
+        let value = match (self.env)(name) {
+            Some(value) => Some(value),
+            None => match self.file.get(&key) {
+                None => None,
+                Some(Value::String(value)) => Some(value.clone()),
+                Some(value @ (Value::Integer(_) | Value::Float(_) | Value::Boolean(_))) => {
+                    Some(value.to_string())
+                }
+                Some(_) => {
+                    self.errors
+                        .push(format!("{} must be a string, number or boolean", key));
+                    None
+                }
+            },
+        };
+        self.read.insert(key);
+        value
+    }
+
+    fn parse<T>(&mut self, name: &str, parse: impl FnOnce(&str) -> Result<T, String>) -> Option<T> {
+        let value = self.value(name)?;
+        parse(&value)
+            .inspect_err(|e| self.errors.push(format!("{} {}", name, e)))
+            .ok()
+    }
+
+    fn get<T>(&mut self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.parse(name, |value| {
+            value
+                .parse()
+                .map_err(|e| format!("is not valid, {}: {}", e, value))
+        })
+    }
+
+    fn seconds(&mut self, name: &str) -> Option<Duration> {
+        self.parse(name, |value| {
+            value
                 .parse()
-                .expect("DATABASE_MAX_CONNECTIONS must be a number"),
-            environment: Environment::from_str(
-                &env::var("ENVIRONMENT").unwrap_or_else(|_| "local".to_string()),
-            ),
-            log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "debug".to_string()),
-            port: env::var("PORT").unwrap_or_else(|_| "3000".to_string()),
+                .map(Duration::from_secs)
+                .map_err(|_| format!("must be a number of seconds: {}", value))
+        })
+    }
+
+    fn check_unknown_keys(&mut self) {
+        for key in self.file.keys() {
+            if !self.read.contains(key) {
+                self.errors
+                    .push(format!("unknown setting {} in config file", key));
+            }
         }
     }
 }
 
+/// Credentials from `user:hash` and `user:hash:role` entries in `CREDENTIALS` and in the file at `CREDENTIALS_FILE`,
+/// entries of the file replace the ones of the same user in the env var.
+fn load_credentials(entries: Option<String>, file: Option<String>) -> Result<Credentials, String> {
+    let mut credentials = Credentials::parse(&entries.unwrap_or_default())
+        .map_err(|e| format!("CREDENTIALS must be user:hash entries, {}", e))?;
+    if let Some(file) = file {
+        let entries = fs::read_to_string(&file)
+            .map_err(|e| format!("failed to read CREDENTIALS_FILE {}: {}", file, e))?;
+        credentials.extend(
+            Credentials::parse(&entries)
+                .map_err(|e| format!("CREDENTIALS_FILE must have user:hash lines, {}", e))?,
+        );
+    }
+    Ok(credentials)
+}
+
+/// Quotas of the route groups from `RATE_LIMIT_API`, `RATE_LIMIT_AUTH` and `RATE_LIMIT_ADMIN`,
+/// each `<requests>/<seconds>` or `off`.
+fn load_rate_limits(settings: &mut Settings<impl Fn(&str) -> Option<String>>) -> RateLimits {
+    let defaults = RateLimits::default();
+    let mut quota = |name: &str, default: Option<Quota>| {
+        settings
+            .parse(name, |value| {
+                Quota::parse(value).map_err(|e| format!("is not valid, {}", e))
+            })
+            .unwrap_or(default)
+    };
+    RateLimits {
+        api: quota("RATE_LIMIT_API", defaults.api),
+        auth: quota("RATE_LIMIT_AUTH", defaults.auth),
+        admin: quota("RATE_LIMIT_ADMIN", defaults.admin),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::env;
 
     #[test]
     fn test_config_new_with_env_vars() {
         env::set_var("DATABASE_URL", "postgres://localhost/test");
         env::set_var("PORT", "4000");
+        env::set_var("METRICS_PORT", "4001");
         env::set_var("LOG_LEVEL", "info");
         env::set_var("ENVIRONMENT", "production");
+        env::set_var("DATABASE_RUN_MIGRATIONS", "false");
+        env::set_var("JWT_EXPIRATION_SECONDS", "60");
+        env::set_var("RATE_LIMIT_AUTH", "5/10");
+        env::set_var("IDEMPOTENCY_TTL_SECONDS", "600");
+        env::set_var("RATE_LIMIT_ADMIN", "off");
+        env::set_var("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318");
 
-        let config = Config::new();
+        let config = Config::load().expect("config is valid");
 
         assert_eq!(
             config.database_url,
             Some(String::from("postgres://localhost/test"))
         );
-        assert_eq!(config.port, "4000");
-        assert_eq!(config.log_level, "info");
+        assert_eq!(config.port, 4000);
+        assert_eq!(config.metrics_port, 4001);
+        assert_eq!(config.log_level, LogLevel::Info);
         assert!(matches!(config.environment, Environment::Production));
+        assert!(!config.database_run_migrations);
+        assert_eq!(config.jwt_expiration, Duration::from_secs(60));
+        assert_eq!(
+            config.rate_limits.auth,
+            Some(Quota {
+                capacity: 5,
+                period: Duration::from_secs(10)
+            })
+        );
+        assert_eq!(config.rate_limits.admin, None);
+        assert_eq!(config.idempotency_ttl, Duration::from_secs(600));
+        assert_eq!(
+            config.otlp_endpoint,
+            Some(String::from("http://localhost:4318"))
+        );
 
         env::remove_var("DATABASE_URL");
         env::remove_var("PORT");
+        env::remove_var("METRICS_PORT");
         env::remove_var("LOG_LEVEL");
         env::remove_var("ENVIRONMENT");
+        env::remove_var("DATABASE_RUN_MIGRATIONS");
+        env::remove_var("JWT_EXPIRATION_SECONDS");
+        env::remove_var("RATE_LIMIT_AUTH");
+        env::remove_var("RATE_LIMIT_ADMIN");
+        env::remove_var("IDEMPOTENCY_TTL_SECONDS");
+        env::remove_var("OTEL_EXPORTER_OTLP_ENDPOINT");
     }
 
     #[test]
     fn test_config_new_without_env_vars() {
         env::remove_var("DATABASE_URL");
         env::remove_var("PORT");
+        env::remove_var("METRICS_PORT");
         env::remove_var("LOG_LEVEL");
         env::remove_var("ENVIRONMENT");
+        env::remove_var("DATABASE_RUN_MIGRATIONS");
+        env::remove_var("JWT_EXPIRATION_SECONDS");
+        env::remove_var("RATE_LIMIT_AUTH");
+        env::remove_var("RATE_LIMIT_ADMIN");
+        env::remove_var("IDEMPOTENCY_TTL_SECONDS");
+        env::remove_var("OTEL_EXPORTER_OTLP_ENDPOINT");
 
-        let config = Config::new();
+        let config = Config::load().expect("config is valid");
 
         assert_eq!(config.database_url, None);
-        assert_eq!(config.port, "3000");
-        assert_eq!(config.log_level, "debug");
+        assert_eq!(config.port, 3000);
+        assert_eq!(config.metrics_port, 3001);
+        assert_eq!(config.log_level, LogLevel::Debug);
         assert!(matches!(config.environment, Environment::Local));
+        assert!(config.database_run_migrations);
+        assert_eq!(config.jwt_secret, None);
+        assert_eq!(config.jwt_expiration, Duration::from_secs(3600));
+        assert_eq!(config.rate_limits.api, RateLimits::default().api);
+        assert_eq!(config.idempotency_ttl, Duration::from_secs(86400));
+        assert_eq!(config.shutdown_drain, Duration::from_secs(5));
+        assert_eq!(config.otlp_endpoint, None);
+        assert_eq!(config.otel_service_name, "axum-postgres");
+    }
+
+    fn from_sources(file: &str, env: &[(&str, &str)]) -> Result<Config, Vec<String>> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Config::from_sources(file.parse().expect("valid TOML"), move |name| {
+            env.get(name).cloned()
+        })
+    }
+
+    #[test]
+    fn test_config_file_with_env_overrides() {
+        let file = r#"
+            database_url = "postgres://todos:secret@db/todos"
+            database_max_connections = 10
+            database_run_migrations = false
+            port = 8080
+            log_level = "warn"
+            rate_limit_api = "off"
+        "#;
+
+        let config = from_sources(file, &[("PORT", "9000")]).expect("config is valid");
+
+        assert_eq!(
+            config.database_url,
+            Some(String::from("postgres://todos:secret@db/todos"))
+        );
+        assert_eq!(config.database_max_connections, 10);
+        assert!(!config.database_run_migrations);
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.log_level, LogLevel::Warn);
+        assert_eq!(config.rate_limits.api, None);
+    }
+
+    #[test]
+    fn test_config_reports_all_errors() {
+        let file = r#"
+            port = 70000
+            log_lvl = "info"
+            database_url = ["postgres://db/todos"]
+        "#;
+        let env = [
+            ("ENVIRONMENT", "staging"),
+            ("DATABASE_MAX_CONNECTIONS", "0"),
+            ("JWT_SECRET", "short"),
+            ("SHUTDOWN_DRAIN_SECONDS", "5s"),
+        ];
+
+        let errors = from_sources(file, &env).err().expect("config is invalid");
+
+        assert_eq!(
+            errors,
+            [
+                "JWT_SECRET must have at least 32 bytes",
+                "SHUTDOWN_DRAIN_SECONDS must be a number of seconds: 5s",
+                "database_url must be a string, number or boolean",
+                "DATABASE_MAX_CONNECTIONS must be a number above zero: 0",
+                "ENVIRONMENT is not valid, must be local or production: staging",
+                "PORT is not valid, number too large to fit in target type: 70000",
+                "unknown setting log_lvl in config file",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_debug_redacts_secrets() {
+        let secret = "a-jwt-secret-of-at-least-32-bytes";
+        let env = [
+            ("DATABASE_URL", "postgres://todos:db-password@db/todos"),
+            ("JWT_SECRET", secret),
+        ];
+
+        let config = from_sources("", &env).expect("config is valid");
+        let debug = format!("{:?}", config);
+
+        assert!(debug.contains("postgres://todos:[redacted]@db/todos"));
+        assert!(!debug.contains("db-password"));
+        assert!(!debug.contains(secret));
+    }
+
+    const PASS_HASH: &str = "$2b$04$driJ439QcPI8nc6H4iHNjO7PIHf70yjjL0aFiT0c9xlbrwaGiayxO";
+
+    #[test]
+    fn test_load_credentials() {
+        let file = env::temp_dir().join(format!("credentials-{}", std::process::id()));
+        fs::write(&file, format!("# users\nfile_user:{}\n", PASS_HASH)).unwrap();
+
+        let credentials = load_credentials(
+            Some(format!("env_user:{}", PASS_HASH)),
+            Some(file.to_string_lossy().to_string()),
+        )
+        .unwrap();
+        fs::remove_file(&file).unwrap();
+
+        assert!(credentials.verify("env_user", "pass").is_some());
+        assert!(credentials.verify("file_user", "pass").is_some());
+        assert!(load_credentials(None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_credentials_plaintext() {
+        let error = load_credentials(Some("user:pass".to_string()), None)
+            .err()
+            .expect("plaintext passwords are rejected");
+        assert!(error.starts_with("CREDENTIALS must be user:hash entries"));
     }
 }
@@ -1,60 +1,578 @@
-use super::{DatabaseError, DbNewTodo, DbTodo, DbUpdateTodo};
-use std::collections::HashMap;
+use super::{
+    models::{
+        DbApiKey, DbBatchOperation, DbBatchResult, DbIdempotencyKey, DbIdempotentResponse, DbList,
+        DbNewApiKey, DbNewIdempotencyKey, DbNewList, DbPoolStatus, DbTagsMatch, DbTodosAfter,
+        DbTodosSort, DbUpdateList, NO_DUE_AT,
+    },
+    text_search_terms, DatabaseError, DbNewTodo, DbTodo, DbTodosQuery, DbUpdateTodo,
+    TodoRepository,
+};
+use async_trait::async_trait;
+use chrono::{SubsecRound, Utc};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Locks are taken in the order of the fields, `todo_map` before `lists`.
 pub struct MemoryDB {
-    todo_map: RwLock<HashMap<Uuid, DbTodo>>,
+    todo_map: RwLock<BTreeMap<Uuid, DbTodo>>,
+    /// Lists without their counts, they are counted when read.
+    lists: RwLock<BTreeMap<Uuid, DbList>>,
+    /// Keys with their hash, in the order they were created.
+    api_keys: RwLock<Vec<(String, DbApiKey)>>,
+    /// Idempotency keys by owner and key.
+    idempotency_keys: RwLock<HashMap<(String, String), DbIdempotencyKey>>,
 }
 
 impl MemoryDB {
     pub fn new() -> Self {
         MemoryDB {
-            todo_map: RwLock::new(HashMap::new()),
+            todo_map: RwLock::new(BTreeMap::new()),
+            lists: RwLock::new(BTreeMap::new()),
+            api_keys: RwLock::new(Vec::new()),
+            idempotency_keys: RwLock::new(HashMap::new()),
         }
     }
+}
 
-    pub async fn get_values(&self) -> Result<Vec<DbTodo>, DatabaseError> {
-        let rows = self.todo_map.read().await.values().cloned().collect();
+#[async_trait]
+impl TodoRepository for MemoryDB {
+    async fn get_values(
+        &self,
+        owner: &str,
+        query: DbTodosQuery,
+    ) -> Result<Vec<DbTodo>, DatabaseError> {
+        let search_terms = query.search.as_deref().map(text_search_terms);
+        let mut rows: Vec<DbTodo> = self
+            .todo_map
+            .read()
+            .await
+            .values()
+            .filter(|todo| todo.owner == owner)
+            .filter(|todo| {
+                query
+                    .list_id
+                    .is_none_or(|list_id| todo.list_id == Some(list_id))
+            })
+            .filter(|todo| {
+                query
+                    .completed
+                    .is_none_or(|completed| todo.completed == completed)
+            })
+            .filter(|todo| {
+                search_terms
+                    .as_ref()
+                    .is_none_or(|terms| matches_search(&todo.text, terms))
+            })
+            .filter(|todo| matches_tags(&todo.tags, &query.tags, query.tags_match))
+            .filter(|todo| {
+                query.created_at.contains(Some(todo.created_at))
+                    && query.updated_at.contains(Some(todo.updated_at))
+                    && query.completed_at.contains(todo.completed_at)
+                    && query.due_at.contains(todo.due_at)
+            })
+            .filter(|todo| {
+                query.after.as_ref().is_none_or(|after| {
+                    compare_sort_key(todo, after, query.sort) == Ordering::Greater
+                })
+            })
+            .cloned()
+            .collect();
+        rows.sort_by(|a, b| compare_todos(a, b, query.sort));
+        rows.truncate(query.limit.try_into().unwrap_or(0));
         Ok(rows)
     }
 
-    pub async fn insert(&self, todo: DbNewTodo) -> Result<DbTodo, DatabaseError> {
-        let todo = DbTodo {
-            id: uuid::Uuid::new_v4(),
-            text: todo.text,
-            completed: false,
-        };
-        self.todo_map.write().await.insert(todo.id, todo.clone());
-        Ok(todo)
+    async fn get(&self, owner: &str, id: Uuid) -> Result<DbTodo, DatabaseError> {
+        let map = self.todo_map.read().await;
+        owned(&map, owner, id).cloned()
     }
 
-    pub async fn remove(&self, id: Uuid) -> Result<(), DatabaseError> {
+    async fn insert(&self, owner: &str, todo: DbNewTodo) -> Result<DbTodo, DatabaseError> {
         let mut map = self.todo_map.write().await;
-        map.remove(&id).ok_or(DatabaseError::NotFound { id })?;
-        Ok(())
+        insert_todo(&mut map, &*self.lists.read().await, owner, todo)
     }
 
-    pub async fn update(&self, id: Uuid, todo: DbUpdateTodo) -> Result<DbTodo, DatabaseError> {
+    async fn remove(
+        &self,
+        owner: &str,
+        id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        remove_todo(
+            &mut *self.todo_map.write().await,
+            owner,
+            id,
+            expected_version,
+        )
+    }
+
+    async fn update(
+        &self,
+        owner: &str,
+        id: Uuid,
+        todo: DbUpdateTodo,
+        expected_version: Option<i64>,
+    ) -> Result<DbTodo, DatabaseError> {
         let mut map = self.todo_map.write().await;
-        if let Some(existing_todo) = map.get_mut(&id) {
-            if let Some(text) = todo.text {
-                existing_todo.text = text;
-            }
-            if let Some(completed) = todo.completed {
-                existing_todo.completed = completed;
+        update_todo(
+            &mut map,
+            &*self.lists.read().await,
+            owner,
+            id,
+            todo,
+            expected_version,
+        )
+    }
+
+    async fn batch(
+        &self,
+        owner: &str,
+        operations: Vec<DbBatchOperation>,
+    ) -> Result<Vec<DbBatchResult>, DatabaseError> {
+        let mut map = self.todo_map.write().await;
+        let lists = self.lists.read().await;
+        // changes are applied to a copy which replaces the map only if all of them succeed
+        let mut changed_map = map.clone();
+        let mut results = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.into_iter().enumerate() {
+            let result = match operation {
+                DbBatchOperation::Insert(todo) => {
+                    insert_todo(&mut changed_map, &lists, owner, todo).map(DbBatchResult::Inserted)
+                }
+                DbBatchOperation::Update {
+                    id,
+                    todo,
+                    expected_version,
+                } => update_todo(&mut changed_map, &lists, owner, id, todo, expected_version)
+                    .map(DbBatchResult::Updated),
+                DbBatchOperation::Remove {
+                    id,
+                    expected_version,
+                } => remove_todo(&mut changed_map, owner, id, expected_version)
+                    .map(|_| DbBatchResult::Removed(id)),
+            };
+            results.push(result.map_err(|e| e.in_batch(index))?);
+        }
+        *map = changed_map;
+        Ok(results)
+    }
+
+    async fn purge(&self, owner: &str) -> Result<u64, DatabaseError> {
+        let mut map = self.todo_map.write().await;
+        let count = map.len();
+        map.retain(|_, todo| todo.owner != owner);
+        Ok((count - map.len()) as u64)
+    }
+
+    async fn count_unowned(&self) -> Result<u64, DatabaseError> {
+        let map = self.todo_map.read().await;
+        Ok(map.values().filter(|todo| todo.owner.is_empty()).count() as u64)
+    }
+
+    async fn assign_unowned(&self, owner: &str) -> Result<u64, DatabaseError> {
+        let mut map = self.todo_map.write().await;
+        let mut count = 0;
+        for todo in map.values_mut().filter(|todo| todo.owner.is_empty()) {
+            todo.owner = owner.to_string();
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn get_lists(&self, owner: &str) -> Result<Vec<DbList>, DatabaseError> {
+        let map = self.todo_map.read().await;
+        let lists = self.lists.read().await;
+        let mut rows: Vec<DbList> = lists
+            .values()
+            .filter(|list| list.owner == owner)
+            .map(|list| with_counts(list, &map))
+            .collect();
+        rows.sort_by_key(|list| (list.created_at, list.id));
+        Ok(rows)
+    }
+
+    async fn get_list(&self, owner: &str, id: Uuid) -> Result<DbList, DatabaseError> {
+        let map = self.todo_map.read().await;
+        let lists = self.lists.read().await;
+        let list = owned_list(&lists, owner, id).ok_or(DatabaseError::NotFound { id })?;
+        Ok(with_counts(list, &map))
+    }
+
+    async fn insert_list(&self, owner: &str, list: DbNewList) -> Result<DbList, DatabaseError> {
+        let now = Utc::now().trunc_subsecs(6);
+        let list = DbList {
+            id: Uuid::new_v4(),
+            owner: owner.to_string(),
+            name: list.name,
+            created_at: now,
+            updated_at: now,
+            todo_count: 0,
+            completed_count: 0,
+        };
+        self.lists.write().await.insert(list.id, list.clone());
+        Ok(list)
+    }
+
+    async fn update_list(
+        &self,
+        owner: &str,
+        id: Uuid,
+        list: DbUpdateList,
+    ) -> Result<DbList, DatabaseError> {
+        let map = self.todo_map.read().await;
+        let mut lists = self.lists.write().await;
+        let existing_list = lists
+            .get_mut(&id)
+            .filter(|list| list.owner == owner)
+            .ok_or(DatabaseError::NotFound { id })?;
+        if let Some(name) = list.name {
+            existing_list.name = name;
+        }
+        existing_list.updated_at = Utc::now().trunc_subsecs(6);
+        Ok(with_counts(existing_list, &map))
+    }
+
+    async fn remove_list(
+        &self,
+        owner: &str,
+        id: Uuid,
+        move_to: Option<Uuid>,
+    ) -> Result<(), DatabaseError> {
+        let mut map = self.todo_map.write().await;
+        let mut lists = self.lists.write().await;
+        check_list(&lists, owner, move_to)?;
+        owned_list(&lists, owner, id).ok_or(DatabaseError::NotFound { id })?;
+        match move_to {
+            Some(move_to) => {
+                let now = Utc::now().trunc_subsecs(6);
+                for todo in map.values_mut().filter(|todo| todo.list_id == Some(id)) {
+                    todo.list_id = Some(move_to);
+                    todo.updated_at = now;
+                    todo.version += 1;
+                }
             }
-            Ok(existing_todo.clone())
-        } else {
-            Err(DatabaseError::NotFound { id })
+            None => map.retain(|_, todo| todo.list_id != Some(id)),
+        }
+        lists.remove(&id);
+        Ok(())
+    }
+
+    async fn insert_api_key(
+        &self,
+        owner: &str,
+        key: DbNewApiKey,
+    ) -> Result<DbApiKey, DatabaseError> {
+        let mut api_keys = self.api_keys.write().await;
+        if api_keys.iter().any(|(hash, _)| *hash == key.key_hash) {
+            return Err(DatabaseError::Internal(anyhow::anyhow!(
+                "api key hash already exists"
+            )));
+        }
+        let api_key = DbApiKey {
+            id: Uuid::new_v4(),
+            owner: owner.to_string(),
+            name: key.name,
+            role: key.role,
+            prefix: key.prefix,
+            created_at: Utc::now().trunc_subsecs(6),
+            revoked_at: None,
+        };
+        api_keys.push((key.key_hash, api_key.clone()));
+        Ok(api_key)
+    }
+
+    async fn get_api_keys(&self, owner: &str) -> Result<Vec<DbApiKey>, DatabaseError> {
+        let api_keys = self.api_keys.read().await;
+        Ok(api_keys
+            .iter()
+            .map(|(_, api_key)| api_key)
+            .filter(|api_key| api_key.owner == owner)
+            .cloned()
+            .collect())
+    }
+
+    async fn revoke_api_key(&self, owner: &str, id: Uuid) -> Result<DbApiKey, DatabaseError> {
+        let mut api_keys = self.api_keys.write().await;
+        let (_, api_key) = api_keys
+            .iter_mut()
+            .find(|(_, api_key)| api_key.id == id && api_key.owner == owner)
+            .ok_or(DatabaseError::NotFound { id })?;
+        api_key
+            .revoked_at
+            .get_or_insert_with(|| Utc::now().trunc_subsecs(6));
+        Ok(api_key.clone())
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<DbApiKey>, DatabaseError> {
+        let api_keys = self.api_keys.read().await;
+        Ok(api_keys
+            .iter()
+            .find(|(hash, api_key)| hash == key_hash && api_key.revoked_at.is_none())
+            .map(|(_, api_key)| api_key.clone()))
+    }
+
+    async fn insert_idempotency_key(
+        &self,
+        owner: &str,
+        key: DbNewIdempotencyKey,
+    ) -> Result<Option<DbIdempotencyKey>, DatabaseError> {
+        let now = Utc::now().trunc_subsecs(6);
+        let mut idempotency_keys = self.idempotency_keys.write().await;
+        idempotency_keys.retain(|_, stored| stored.expires_at > now);
+
+        let map_key = (owner.to_string(), key.key.clone());
+        if let Some(stored) = idempotency_keys.get(&map_key) {
+            return Ok(Some(stored.clone()));
+        }
+        idempotency_keys.insert(
+            map_key,
+            DbIdempotencyKey {
+                owner: owner.to_string(),
+                key: key.key,
+                request_hash: key.request_hash,
+                response_status: None,
+                response_body: None,
+                created_at: now,
+                expires_at: key.expires_at.trunc_subsecs(6),
+            },
+        );
+        Ok(None)
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        owner: &str,
+        key: &str,
+        response: DbIdempotentResponse,
+    ) -> Result<(), DatabaseError> {
+        let mut idempotency_keys = self.idempotency_keys.write().await;
+        if let Some(stored) = idempotency_keys.get_mut(&(owner.to_string(), key.to_string())) {
+            stored.response_status = Some(response.status);
+            stored.response_body = Some(response.body);
+            stored.expires_at = response.expires_at.trunc_subsecs(6);
         }
+        Ok(())
+    }
+
+    async fn remove_idempotency_key(&self, owner: &str, key: &str) -> Result<(), DatabaseError> {
+        let mut idempotency_keys = self.idempotency_keys.write().await;
+        idempotency_keys.remove(&(owner.to_string(), key.to_string()));
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<Option<DbPoolStatus>, DatabaseError> {
+        Ok(None)
+    }
+
+    fn pool_status(&self) -> Option<DbPoolStatus> {
+        None
+    }
+}
+
+/// Todo with the id if it belongs to the owner.
+fn owned<'a>(
+    map: &'a BTreeMap<Uuid, DbTodo>,
+    owner: &str,
+    id: Uuid,
+) -> Result<&'a DbTodo, DatabaseError> {
+    map.get(&id)
+        .filter(|todo| todo.owner == owner)
+        .ok_or(DatabaseError::NotFound { id })
+}
+
+/// List with the id if it belongs to the owner.
+fn owned_list<'a>(lists: &'a BTreeMap<Uuid, DbList>, owner: &str, id: Uuid) -> Option<&'a DbList> {
+    lists.get(&id).filter(|list| list.owner == owner)
+}
+
+fn check_list(
+    lists: &BTreeMap<Uuid, DbList>,
+    owner: &str,
+    list_id: Option<Uuid>,
+) -> Result<(), DatabaseError> {
+    match list_id {
+        Some(id) if owned_list(lists, owner, id).is_none() => {
+            Err(DatabaseError::ListNotFound { id })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn with_counts(list: &DbList, map: &BTreeMap<Uuid, DbTodo>) -> DbList {
+    let todos = map.values().filter(|todo| todo.list_id == Some(list.id));
+    let (todo_count, completed_count) = todos.fold((0, 0), |(count, completed), todo| {
+        (count + 1, completed + i64::from(todo.completed))
+    });
+    DbList {
+        todo_count,
+        completed_count,
+        ..list.clone()
+    }
+}
+
+fn insert_todo(
+    map: &mut BTreeMap<Uuid, DbTodo>,
+    lists: &BTreeMap<Uuid, DbList>,
+    owner: &str,
+    todo: DbNewTodo,
+) -> Result<DbTodo, DatabaseError> {
+    check_list(lists, owner, todo.list_id)?;
+    // Postgres stores timestamps with microsecond precision
+    let now = Utc::now().trunc_subsecs(6);
+    let todo = DbTodo {
+        id: uuid::Uuid::new_v4(),
+        text: todo.text,
+        completed: false,
+        created_at: now,
+        updated_at: now,
+        completed_at: None,
+        version: 1,
+        owner: owner.to_string(),
+        list_id: todo.list_id,
+        tags: unique_tags(todo.tags),
+        due_at: todo.due_at.map(|due_at| due_at.trunc_subsecs(6)),
+        priority: todo.priority,
+    };
+    map.insert(todo.id, todo.clone());
+    Ok(todo)
+}
+
+fn remove_todo(
+    map: &mut BTreeMap<Uuid, DbTodo>,
+    owner: &str,
+    id: Uuid,
+    expected_version: Option<i64>,
+) -> Result<(), DatabaseError> {
+    let existing_todo = owned(map, owner, id)?;
+    check_version(existing_todo, expected_version)?;
+    map.remove(&id);
+    Ok(())
+}
+
+fn update_todo(
+    map: &mut BTreeMap<Uuid, DbTodo>,
+    lists: &BTreeMap<Uuid, DbList>,
+    owner: &str,
+    id: Uuid,
+    todo: DbUpdateTodo,
+    expected_version: Option<i64>,
+) -> Result<DbTodo, DatabaseError> {
+    check_list(lists, owner, todo.list_id.flatten())?;
+    let existing_todo = map.get_mut(&id).filter(|todo| todo.owner == owner);
+    if let Some(existing_todo) = existing_todo {
+        check_version(existing_todo, expected_version)?;
+        if let Some(text) = todo.text {
+            existing_todo.text = text;
+        }
+        if let Some(list_id) = todo.list_id {
+            existing_todo.list_id = list_id;
+        }
+        if let Some(tags) = todo.tags {
+            existing_todo.tags = unique_tags(tags);
+        }
+        if let Some(due_at) = todo.due_at {
+            existing_todo.due_at = due_at.map(|due_at| due_at.trunc_subsecs(6));
+        }
+        if let Some(priority) = todo.priority {
+            existing_todo.priority = priority;
+        }
+        let now = Utc::now().trunc_subsecs(6);
+        if let Some(completed) = todo.completed {
+            existing_todo.completed = completed;
+            existing_todo.completed_at = if completed {
+                existing_todo.completed_at.or(Some(now))
+            } else {
+                None
+            };
+        }
+        existing_todo.updated_at = now;
+        existing_todo.version += 1;
+        Ok(existing_todo.clone())
+    } else {
+        Err(DatabaseError::NotFound { id })
+    }
+}
+
+fn check_version(todo: &DbTodo, expected_version: Option<i64>) -> Result<(), DatabaseError> {
+    match expected_version {
+        Some(expected_version) if expected_version != todo.version => {
+            Err(DatabaseError::VersionMismatch {
+                id: todo.id,
+                expected_version,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Compares todos in the order given by `sort`, same as the Postgres ORDER BY.
+fn compare_todos(a: &DbTodo, b: &DbTodo, sort: DbTodosSort) -> Ordering {
+    match sort {
+        DbTodosSort::Id => a.id.cmp(&b.id),
+        DbTodosSort::Text => (&a.text, a.id).cmp(&(&b.text, b.id)),
+        DbTodosSort::TextDesc => (&b.text, b.id).cmp(&(&a.text, a.id)),
+        DbTodosSort::CreatedAt => (a.created_at, a.id).cmp(&(b.created_at, b.id)),
+        DbTodosSort::CreatedAtDesc => (b.created_at, b.id).cmp(&(a.created_at, a.id)),
+        DbTodosSort::UpdatedAt => (a.updated_at, a.id).cmp(&(b.updated_at, b.id)),
+        DbTodosSort::UpdatedAtDesc => (b.updated_at, b.id).cmp(&(a.updated_at, a.id)),
+        DbTodosSort::DueAt => (a.due_at_key(), a.id).cmp(&(b.due_at_key(), b.id)),
+        DbTodosSort::DueAtDesc => (b.due_at_key(), b.id).cmp(&(a.due_at_key(), a.id)),
+        DbTodosSort::Priority => (a.priority, a.id).cmp(&(b.priority, b.id)),
+        DbTodosSort::PriorityDesc => (b.priority, b.id).cmp(&(a.priority, a.id)),
+    }
+}
+
+/// Compares a todo to the cursor of the previous page in the order given by `sort`.
+fn compare_sort_key(todo: &DbTodo, after: &DbTodosAfter, sort: DbTodosSort) -> Ordering {
+    let after_text = after.text.as_deref().unwrap_or_default();
+    let after_created_at = after.created_at.unwrap_or_default();
+    let after_updated_at = after.updated_at.unwrap_or_default();
+    let after_due_at = after.due_at.unwrap_or(NO_DUE_AT);
+    let after_priority = after.priority.unwrap_or_default();
+    match sort {
+        DbTodosSort::Id => todo.id.cmp(&after.id),
+        DbTodosSort::Text => (todo.text.as_str(), todo.id).cmp(&(after_text, after.id)),
+        DbTodosSort::TextDesc => (after_text, after.id).cmp(&(todo.text.as_str(), todo.id)),
+        DbTodosSort::CreatedAt => (todo.created_at, todo.id).cmp(&(after_created_at, after.id)),
+        DbTodosSort::CreatedAtDesc => (after_created_at, after.id).cmp(&(todo.created_at, todo.id)),
+        DbTodosSort::UpdatedAt => (todo.updated_at, todo.id).cmp(&(after_updated_at, after.id)),
+        DbTodosSort::UpdatedAtDesc => (after_updated_at, after.id).cmp(&(todo.updated_at, todo.id)),
+        DbTodosSort::DueAt => (todo.due_at_key(), todo.id).cmp(&(after_due_at, after.id)),
+        DbTodosSort::DueAtDesc => (after_due_at, after.id).cmp(&(todo.due_at_key(), todo.id)),
+        DbTodosSort::Priority => (todo.priority, todo.id).cmp(&(after_priority, after.id)),
+        DbTodosSort::PriorityDesc => (after_priority, after.id).cmp(&(todo.priority, todo.id)),
+    }
+}
+
+/// All search terms must be found as words of the text, like `plainto_tsquery`.
+fn matches_search(text: &str, search_terms: &[String]) -> bool {
+    let words = text_search_terms(text);
+    !search_terms.is_empty() && search_terms.iter().all(|term| words.contains(term))
+}
+
+/// Tags in ascending order without duplicates, like the tags read from the other databases.
+fn unique_tags(mut tags: Vec<String>) -> Vec<String> {
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn matches_tags(todo_tags: &[String], tags: &[String], tags_match: DbTagsMatch) -> bool {
+    if tags.is_empty() {
+        return true;
+    }
+    match tags_match {
+        DbTagsMatch::All => tags.iter().all(|tag| todo_tags.contains(tag)),
+        DbTagsMatch::Any => tags.iter().any(|tag| todo_tags.contains(tag)),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::datasources::database::{
-        memory_db::MemoryDB, DatabaseError, DbNewTodo, DbUpdateTodo,
+        memory_db::MemoryDB, DatabaseError, DbNewTodo, DbTodosQuery, DbUpdateTodo, TodoRepository,
     };
     use uuid::Uuid;
 
@@ -63,10 +581,14 @@ mod tests {
         let db = MemoryDB::new();
         let new_todo = DbNewTodo {
             text: String::from("Test todo"),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        db.insert(new_todo).await.unwrap();
+        db.insert("user", new_todo).await.unwrap();
 
-        let todos = db.get_values().await.unwrap();
+        let todos = db.get_values("user", all_todos()).await.unwrap();
         assert_eq!(todos.len(), 1);
         assert_eq!(todos[0].text, "Test todo");
     }
@@ -76,8 +598,12 @@ mod tests {
         let db = MemoryDB::new();
         let new_todo = DbNewTodo {
             text: String::from("Test todo"),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        let inserted_todo = db.insert(new_todo).await.unwrap();
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
 
         assert_eq!(inserted_todo.text, "Test todo");
         assert!(!inserted_todo.completed);
@@ -88,18 +614,22 @@ mod tests {
         let db = MemoryDB::new();
         let new_todo = DbNewTodo {
             text: String::from("Test todo"),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        let inserted_todo = db.insert(new_todo).await.unwrap();
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
 
-        db.remove(inserted_todo.id).await.unwrap();
-        let todos = db.get_values().await.unwrap();
+        db.remove("user", inserted_todo.id, None).await.unwrap();
+        let todos = db.get_values("user", all_todos()).await.unwrap();
         assert!(todos.is_empty());
     }
 
     #[tokio::test]
     async fn test_remove_not_found() {
         let db = MemoryDB::new();
-        let result = db.remove(Uuid::new_v4()).await;
+        let result = db.remove("user", Uuid::new_v4(), None).await;
         assert!(result.is_err());
         assert!(matches!(
             result.err().unwrap(),
@@ -112,14 +642,25 @@ mod tests {
         let db = MemoryDB::new();
         let new_todo = DbNewTodo {
             text: String::from("Test todo"),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        let inserted_todo = db.insert(new_todo).await.unwrap();
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
 
         let update_todo = DbUpdateTodo {
             text: Some(String::from("Updated todo")),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
-        let updated_todo = db.update(inserted_todo.id, update_todo).await.unwrap();
+        let updated_todo = db
+            .update("user", inserted_todo.id, update_todo, None)
+            .await
+            .unwrap();
 
         assert_eq!(updated_todo.text, "Updated todo");
         assert!(updated_todo.completed);
@@ -131,12 +672,23 @@ mod tests {
         let update_todo = DbUpdateTodo {
             text: Some(String::from("Updated todo")),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
-        let result = db.update(Uuid::new_v4(), update_todo).await;
+        let result = db.update("user", Uuid::new_v4(), update_todo, None).await;
         assert!(result.is_err());
         assert!(matches!(
             result.err().unwrap(),
             DatabaseError::NotFound { id: _ }
         ));
     }
+
+    fn all_todos() -> DbTodosQuery {
+        DbTodosQuery {
+            limit: 100,
+            ..Default::default()
+        }
+    }
 }
@@ -1,4 +1,8 @@
-use crate::server::domain::todos::{NewTodo, UpdateTodo};
+use crate::server::domain::{
+    lists::{NewList, UpdateList},
+    todos::{NewTodo, ReplaceTodo, UpdateTodo},
+};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -8,16 +12,160 @@ pub struct DbTodo {
     pub id: Uuid,
     pub text: String,
     pub completed: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Set while the todo is completed, to the time it was last marked as completed.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Starts at 1 and is incremented by every update.
+    pub version: i64,
+    /// User who created the todo.
+    pub owner: String,
+    /// List the todo belongs to, if any.
+    pub list_id: Option<Uuid>,
+    /// Names of the tags of the todo in ascending order, read with a separate query.
+    #[sqlx(skip)]
+    pub tags: Vec<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: DbPriority,
+}
+
+/// Priority of a todo, stored as its number so that it sorts from low to high.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, sqlx::Type)]
+#[repr(i16)]
+pub enum DbPriority {
+    Low = 0,
+    #[default]
+    Medium = 1,
+    High = 2,
+}
+
+/// Sort key of todos without a due date, which sort after the todos with one. Due dates are
+/// validated to be earlier.
+pub const NO_DUE_AT: DateTime<Utc> =
+    DateTime::from_timestamp(253402300799, 0).expect("9999-12-31T23:59:59Z is a valid time");
+
+impl DbTodo {
+    /// Due date as sorted, `NO_DUE_AT` for todos without one.
+    pub fn due_at_key(&self) -> DateTime<Utc> {
+        self.due_at.unwrap_or(NO_DUE_AT)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DbTodosSort {
+    #[default]
+    Id,
+    Text,
+    TextDesc,
+    CreatedAt,
+    CreatedAtDesc,
+    UpdatedAt,
+    UpdatedAtDesc,
+    /// Todos without a due date last.
+    DueAt,
+    DueAtDesc,
+    Priority,
+    PriorityDesc,
+}
+
+impl DbTodosSort {
+    pub fn is_descending(self) -> bool {
+        matches!(
+            self,
+            DbTodosSort::TextDesc
+                | DbTodosSort::CreatedAtDesc
+                | DbTodosSort::UpdatedAtDesc
+                | DbTodosSort::DueAtDesc
+                | DbTodosSort::PriorityDesc
+        )
+    }
+}
+
+/// Sort key of the last row of the previous page. The field matching the
+/// requested sort is always set, ties are broken by id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbTodosAfter {
+    pub id: Uuid,
+    pub text: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// `NO_DUE_AT` if the last row has no due date.
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Option<DbPriority>,
+}
+
+/// How the todos are matched against the tags of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DbTagsMatch {
+    /// Todos with all the tags.
+    #[default]
+    All,
+    /// Todos with at least one of the tags.
+    Any,
+}
+
+/// Exclusive bounds on a timestamp, an unset bound matches any time.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DbTimeRange {
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl DbTimeRange {
+    pub fn is_unbounded(&self) -> bool {
+        self.after.is_none() && self.before.is_none()
+    }
+
+    /// Missing timestamps are only contained in an unbounded range.
+    pub fn contains(&self, time: Option<DateTime<Utc>>) -> bool {
+        match time {
+            Some(time) => {
+                self.after.is_none_or(|after| time > after)
+                    && self.before.is_none_or(|before| time < before)
+            }
+            None => self.is_unbounded(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DbTodosQuery {
+    pub limit: i64,
+    pub after: Option<DbTodosAfter>,
+    pub completed: Option<bool>,
+    pub search: Option<String>,
+    pub sort: DbTodosSort,
+    pub created_at: DbTimeRange,
+    pub updated_at: DbTimeRange,
+    pub completed_at: DbTimeRange,
+    /// Todos without a due date are only returned for an unbounded range.
+    pub due_at: DbTimeRange,
+    /// Return only the todos of the list.
+    pub list_id: Option<Uuid>,
+    /// Return only the todos with these tags, matched by `tags_match`. Expected without
+    /// duplicates, ignored if empty.
+    pub tags: Vec<String>,
+    pub tags_match: DbTagsMatch,
 }
 
 pub struct DbNewTodo {
     pub text: String,
+    /// Fails with `DatabaseError::ListNotFound` unless the owner has the list.
+    pub list_id: Option<Uuid>,
+    /// Tags are created for the owner as needed, duplicates are ignored.
+    pub tags: Vec<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: DbPriority,
 }
 
 impl From<NewTodo> for DbNewTodo {
     fn from(new_todo: NewTodo) -> Self {
         DbNewTodo {
             text: new_todo.text,
+            list_id: new_todo.list_id,
+            tags: new_todo.tags,
+            due_at: new_todo.due_at,
+            priority: new_todo.priority.map(Into::into).unwrap_or_default(),
         }
     }
 }
@@ -25,6 +173,13 @@ impl From<NewTodo> for DbNewTodo {
 pub struct DbUpdateTodo {
     pub text: Option<String>,
     pub completed: Option<bool>,
+    /// Moves the todo to the list, or out of any list with `Some(None)`.
+    pub list_id: Option<Option<Uuid>>,
+    /// Replaces all the tags of the todo, created like the tags of `DbNewTodo`.
+    pub tags: Option<Vec<String>>,
+    /// Sets the due date, or removes it with `Some(None)`.
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub priority: Option<DbPriority>,
 }
 
 impl From<UpdateTodo> for DbUpdateTodo {
@@ -32,6 +187,141 @@ impl From<UpdateTodo> for DbUpdateTodo {
         DbUpdateTodo {
             text: update_todo.text,
             completed: update_todo.completed,
+            list_id: update_todo.list_id.map(Some),
+            tags: update_todo.tags,
+            due_at: update_todo.due_at.map(Some),
+            priority: update_todo.priority.map(Into::into),
+        }
+    }
+}
+
+impl From<ReplaceTodo> for DbUpdateTodo {
+    fn from(replace_todo: ReplaceTodo) -> Self {
+        DbUpdateTodo {
+            text: Some(replace_todo.text),
+            completed: Some(replace_todo.completed),
+            list_id: Some(replace_todo.list_id),
+            tags: Some(replace_todo.tags),
+            due_at: Some(replace_todo.due_at),
+            priority: Some(replace_todo.priority.into()),
         }
     }
 }
+
+/// List of todos with the counts of its todos.
+#[derive(Debug, FromRow, Serialize, Clone, PartialEq)]
+pub struct DbList {
+    pub id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub todo_count: i64,
+    pub completed_count: i64,
+}
+
+pub struct DbNewList {
+    pub name: String,
+}
+
+impl From<NewList> for DbNewList {
+    fn from(new_list: NewList) -> Self {
+        DbNewList {
+            name: new_list.name,
+        }
+    }
+}
+
+pub struct DbUpdateList {
+    pub name: Option<String>,
+}
+
+impl From<UpdateList> for DbUpdateList {
+    fn from(update_list: UpdateList) -> Self {
+        DbUpdateList {
+            name: update_list.name,
+        }
+    }
+}
+
+/// One change of a batch, applied in the order of the batch.
+pub enum DbBatchOperation {
+    Insert(DbNewTodo),
+    Update {
+        id: Uuid,
+        todo: DbUpdateTodo,
+        expected_version: Option<i64>,
+    },
+    Remove {
+        id: Uuid,
+        expected_version: Option<i64>,
+    },
+}
+
+/// Result of a `DbBatchOperation`, at the same index as the operation.
+#[derive(Debug, Clone)]
+pub enum DbBatchResult {
+    Inserted(DbTodo),
+    Updated(DbTodo),
+    Removed(Uuid),
+}
+
+/// API key of a user, the key itself is not stored.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct DbApiKey {
+    pub id: Uuid,
+    pub owner: String,
+    pub name: String,
+    /// Role the key authenticates with.
+    pub role: String,
+    /// Start of the key, to recognize it in a list of keys.
+    pub prefix: String,
+    pub created_at: DateTime<Utc>,
+    /// Set once the key is revoked, revoked keys are not accepted anymore.
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+pub struct DbNewApiKey {
+    pub name: String,
+    pub role: String,
+    pub prefix: String,
+    /// Hex encoded SHA-256 hash of the key, keys are looked up by it.
+    pub key_hash: String,
+}
+
+/// `Idempotency-Key` of a request of the owner.
+#[derive(Debug, FromRow, Clone, PartialEq)]
+pub struct DbIdempotencyKey {
+    pub owner: String,
+    pub key: String,
+    /// Hex encoded SHA-256 hash of the request, retries must have the same request.
+    pub request_hash: String,
+    /// Status and body of the response, unset while the request is in progress.
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// The key is released and can be used for another request after this time.
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct DbNewIdempotencyKey {
+    pub key: String,
+    pub request_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Response of the request of an idempotency key, kept until `expires_at`.
+pub struct DbIdempotentResponse {
+    pub status: i32,
+    pub body: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Connections of the pool of a database.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DbPoolStatus {
+    /// Open connections, idle or in use.
+    pub size: u32,
+    pub idle: u32,
+    pub max_size: u32,
+}
@@ -1,12 +1,30 @@
 use super::{
-    models::{DbNewTodo, DbTodo, DbUpdateTodo},
-    DatabaseError,
+    check_migrations_version,
+    models::{
+        DbApiKey, DbBatchOperation, DbBatchResult, DbIdempotencyKey, DbIdempotentResponse, DbList,
+        DbNewApiKey, DbNewIdempotencyKey, DbNewList, DbNewTodo, DbPoolStatus, DbTagsMatch,
+        DbTimeRange, DbTodo, DbTodosQuery, DbTodosSort, DbUpdateList, DbUpdateTodo, NO_DUE_AT,
+    },
+    DatabaseError, TodoRepository,
 };
 use anyhow::Context;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
-use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{SubsecRound, Utc};
+use sqlx::{
+    migrate::Migrator,
+    pool::PoolConnection,
+    postgres::{PgConnection, PgPoolOptions},
+    Connection, Encode, Pool, Postgres, QueryBuilder, Type,
+};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tracing::instrument;
 use uuid::Uuid;
 
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 pub struct PostgresDB {
     pool: Pool<Postgres>,
 }
@@ -21,66 +39,944 @@ impl PostgresDB {
         Ok(PostgresDB { pool })
     }
 
-    pub async fn get_values(&self) -> Result<Vec<DbTodo>, DatabaseError> {
-        let rows = sqlx::query_as::<_, DbTodo>("SELECT id, text, completed FROM todos")
-            .fetch_all(&self.pool)
+    /// Fails if the database has migrations applied that this binary does not know about,
+    /// e.g. after a rollback to an older release.
+    pub async fn check_schema_version(&self) -> anyhow::Result<()> {
+        let applied: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+            .fetch_one(&self.pool)
+            .await
+            .or_else(|e| match e {
+                // undefined_table, no migrations have been run yet
+                sqlx::Error::Database(ref db_error)
+                    if db_error.code().as_deref() == Some("42P01") =>
+                {
+                    Ok(None)
+                }
+                e => Err(e),
+            })
+            .context("failed to read database schema version")?;
+        check_migrations_version(&MIGRATOR, applied)
+    }
+
+    pub async fn migrate(&self) -> anyhow::Result<()> {
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .context("failed to run database migrations")?;
+        Ok(())
+    }
+
+    async fn acquire(&self) -> Result<PoolConnection<Postgres>, DatabaseError> {
+        let start = Instant::now();
+        let connection = self.pool.acquire().await;
+        metrics::histogram!("db_pool_acquire_duration_seconds")
+            .record(start.elapsed().as_secs_f64());
+        Ok(connection.context("failed to acquire connection")?)
+    }
+}
+
+/// Records the latency of a repository method once dropped, so failed and cancelled calls are
+/// measured as well.
+struct QueryTimer {
+    method: &'static str,
+    start: Instant,
+}
+
+impl QueryTimer {
+    fn start(method: &'static str) -> Self {
+        QueryTimer {
+            method,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        metrics::histogram!("db_query_duration_seconds", "method" => self.method)
+            .record(self.start.elapsed().as_secs_f64());
+    }
+}
+
+// every method is a child span of the request, named after the method and with it as
+// `db.operation`, like the query metrics
+#[async_trait]
+impl TodoRepository for PostgresDB {
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "get_values", otel.kind = "client")
+    )]
+    async fn get_values(
+        &self,
+        owner: &str,
+        query: DbTodosQuery,
+    ) -> Result<Vec<DbTodo>, DatabaseError> {
+        let _timer = QueryTimer::start("get_values");
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority FROM todos WHERE owner = ",
+        );
+        builder.push_bind(owner.to_string());
+        if let Some(list_id) = query.list_id {
+            builder.push(" AND list_id = ").push_bind(list_id);
+        }
+        if let Some(completed) = query.completed {
+            builder.push(" AND completed = ").push_bind(completed);
+        }
+        if let Some(search) = query.search {
+            // uses the todos_text_search_idx index
+            builder
+                .push(" AND to_tsvector('simple', text) @@ plainto_tsquery('simple', ")
+                .push_bind(search)
+                .push(")");
+        }
+        if !query.tags.is_empty() {
+            let count = query.tags.len() as i64;
+            // the tags of the query and of a todo are unique, so a todo with all of them has
+            // one matching tag for each
+            builder
+                .push(" AND (SELECT COUNT(*) FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id = todos.id AND tags.name = ANY(")
+                .push_bind(query.tags)
+                .push("))");
+            match query.tags_match {
+                DbTagsMatch::All => builder.push(" = ").push_bind(count),
+                DbTagsMatch::Any => builder.push(" > 0"),
+            };
+        }
+        push_time_range(&mut builder, "created_at", query.created_at);
+        push_time_range(&mut builder, "updated_at", query.updated_at);
+        push_time_range(&mut builder, "completed_at", query.completed_at);
+        push_time_range(&mut builder, "due_at", query.due_at);
+        if let Some(after) = query.after {
+            match query.sort {
+                DbTodosSort::Id => {
+                    builder.push(" AND id > ").push_bind(after.id);
+                }
+                DbTodosSort::Text | DbTodosSort::TextDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.text.unwrap_or_default(),
+                    after.id,
+                ),
+                DbTodosSort::CreatedAt | DbTodosSort::CreatedAtDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.created_at.unwrap_or_default(),
+                    after.id,
+                ),
+                DbTodosSort::UpdatedAt | DbTodosSort::UpdatedAtDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.updated_at.unwrap_or_default(),
+                    after.id,
+                ),
+                DbTodosSort::DueAt | DbTodosSort::DueAtDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.due_at.unwrap_or(NO_DUE_AT),
+                    after.id,
+                ),
+                DbTodosSort::Priority | DbTodosSort::PriorityDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.priority.unwrap_or_default(),
+                    after.id,
+                ),
+            }
+        }
+        match query.sort {
+            DbTodosSort::Id => builder.push(" ORDER BY id"),
+            sort if sort.is_descending() => builder.push(format_args!(
+                " ORDER BY {} DESC, id DESC",
+                sort_column(sort)
+            )),
+            sort => builder.push(format_args!(" ORDER BY {}, id", sort_column(sort))),
+        };
+        builder.push(" LIMIT ").push_bind(query.limit);
+
+        let mut connection = self.acquire().await?;
+        let mut rows = builder
+            .build_query_as::<DbTodo>()
+            .fetch_all(&mut *connection)
             .await
             .context("failed to fetch todos")?;
+        load_tags(&mut connection, &mut rows).await?;
         Ok(rows)
     }
 
-    pub async fn insert(&self, todo: DbNewTodo) -> Result<DbTodo, DatabaseError> {
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "get", otel.kind = "client")
+    )]
+    async fn get(&self, owner: &str, id: Uuid) -> Result<DbTodo, DatabaseError> {
+        let _timer = QueryTimer::start("get");
+        let mut connection = self.acquire().await?;
         let row = sqlx::query_as::<_, DbTodo>(
-            "INSERT INTO todos (id, text, completed) VALUES ($1, $2, $3) RETURNING id, text, completed",
+            "SELECT id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority FROM todos WHERE id = $1 AND owner = $2",
         )
-        .bind(uuid::Uuid::new_v4())
-        .bind(todo.text)
-        .bind(false) // default completed to false
-        .fetch_one(&self.pool)
+        .bind(id)
+        .bind(owner)
+        .fetch_optional(&mut *connection)
         .await
-        .context("failed to insert todo")?;
+        .context("failed to fetch todo")?;
+        let mut row = row.ok_or(DatabaseError::NotFound { id })?;
+        load_tags(&mut connection, std::slice::from_mut(&mut row)).await?;
         Ok(row)
     }
 
-    pub async fn remove(&self, id: Uuid) -> Result<(), DatabaseError> {
-        let result = sqlx::query("DELETE FROM todos WHERE id = $1")
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "insert", otel.kind = "client")
+    )]
+    async fn insert(&self, owner: &str, todo: DbNewTodo) -> Result<DbTodo, DatabaseError> {
+        let _timer = QueryTimer::start("insert");
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        let row = insert_todo(&mut transaction, owner, todo).await?;
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(row)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "remove", otel.kind = "client")
+    )]
+    async fn remove(
+        &self,
+        owner: &str,
+        id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        let _timer = QueryTimer::start("remove");
+        let mut connection = self.acquire().await?;
+        remove_todo(&mut connection, owner, id, expected_version).await
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "update", otel.kind = "client")
+    )]
+    async fn update(
+        &self,
+        owner: &str,
+        id: Uuid,
+        todo: DbUpdateTodo,
+        expected_version: Option<i64>,
+    ) -> Result<DbTodo, DatabaseError> {
+        let _timer = QueryTimer::start("update");
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        let row = update_todo(&mut transaction, owner, id, todo, expected_version).await?;
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(row)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "batch", otel.kind = "client")
+    )]
+    async fn batch(
+        &self,
+        owner: &str,
+        operations: Vec<DbBatchOperation>,
+    ) -> Result<Vec<DbBatchResult>, DatabaseError> {
+        let _timer = QueryTimer::start("batch");
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        let mut results = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.into_iter().enumerate() {
+            let result = match operation {
+                DbBatchOperation::Insert(todo) => insert_todo(&mut transaction, owner, todo)
+                    .await
+                    .map(DbBatchResult::Inserted),
+                DbBatchOperation::Update {
+                    id,
+                    todo,
+                    expected_version,
+                } => update_todo(&mut transaction, owner, id, todo, expected_version)
+                    .await
+                    .map(DbBatchResult::Updated),
+                DbBatchOperation::Remove {
+                    id,
+                    expected_version,
+                } => remove_todo(&mut transaction, owner, id, expected_version)
+                    .await
+                    .map(|_| DbBatchResult::Removed(id)),
+            };
+            // the transaction is rolled back when dropped
+            results.push(result.map_err(|e| e.in_batch(index))?);
+        }
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(results)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "purge", otel.kind = "client")
+    )]
+    async fn purge(&self, owner: &str) -> Result<u64, DatabaseError> {
+        let _timer = QueryTimer::start("purge");
+        let mut connection = self.acquire().await?;
+        let result = sqlx::query("DELETE FROM todos WHERE owner = $1")
+            .bind(owner)
+            .execute(&mut *connection)
+            .await
+            .context("failed to purge todos")?;
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "count_unowned", otel.kind = "client")
+    )]
+    async fn count_unowned(&self) -> Result<u64, DatabaseError> {
+        let _timer = QueryTimer::start("count_unowned");
+        let mut connection = self.acquire().await?;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM todos WHERE owner = ''")
+            .fetch_one(&mut *connection)
+            .await
+            .context("failed to count unowned todos")?;
+        Ok(count as u64)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "assign_unowned", otel.kind = "client")
+    )]
+    async fn assign_unowned(&self, owner: &str) -> Result<u64, DatabaseError> {
+        let _timer = QueryTimer::start("assign_unowned");
+        let mut connection = self.acquire().await?;
+        let result = sqlx::query("UPDATE todos SET owner = $1 WHERE owner = ''")
+            .bind(owner)
+            .execute(&mut *connection)
+            .await
+            .context("failed to assign unowned todos")?;
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "get_lists", otel.kind = "client")
+    )]
+    async fn get_lists(&self, owner: &str) -> Result<Vec<DbList>, DatabaseError> {
+        let _timer = QueryTimer::start("get_lists");
+        let mut connection = self.acquire().await?;
+        let lists = sqlx::query_as::<_, DbList>(SELECT_LISTS)
+            .bind(owner)
+            .bind(None::<Uuid>)
+            .fetch_all(&mut *connection)
+            .await
+            .context("failed to get lists")?;
+        Ok(lists)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "get_list", otel.kind = "client")
+    )]
+    async fn get_list(&self, owner: &str, id: Uuid) -> Result<DbList, DatabaseError> {
+        let _timer = QueryTimer::start("get_list");
+        let mut connection = self.acquire().await?;
+        select_list(&mut connection, owner, id).await
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "insert_list", otel.kind = "client")
+    )]
+    async fn insert_list(&self, owner: &str, list: DbNewList) -> Result<DbList, DatabaseError> {
+        let _timer = QueryTimer::start("insert_list");
+        let mut connection = self.acquire().await?;
+        let list = sqlx::query_as::<_, DbList>(
+            "INSERT INTO lists (id, owner, name) VALUES ($1, $2, $3) RETURNING id, owner, name, created_at, updated_at, 0::BIGINT AS todo_count, 0::BIGINT AS completed_count",
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner)
+        .bind(list.name)
+        .fetch_one(&mut *connection)
+        .await
+        .context("failed to insert list")?;
+        Ok(list)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "update_list", otel.kind = "client")
+    )]
+    async fn update_list(
+        &self,
+        owner: &str,
+        id: Uuid,
+        list: DbUpdateList,
+    ) -> Result<DbList, DatabaseError> {
+        let _timer = QueryTimer::start("update_list");
+        let mut connection = self.acquire().await?;
+        let result = sqlx::query(
+            "UPDATE lists SET name = COALESCE($1, name), updated_at = now() WHERE id = $2 AND owner = $3",
+        )
+            .bind(list.name)
+            .bind(id)
+            .bind(owner)
+            .execute(&mut *connection)
+            .await
+            .context("failed to update list")?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound { id });
+        }
+        select_list(&mut connection, owner, id).await
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "remove_list", otel.kind = "client")
+    )]
+    async fn remove_list(
+        &self,
+        owner: &str,
+        id: Uuid,
+        move_to: Option<Uuid>,
+    ) -> Result<(), DatabaseError> {
+        let _timer = QueryTimer::start("remove_list");
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        // the transaction is rolled back when dropped
+        if let Some(move_to) = move_to {
+            check_list(&mut transaction, owner, move_to).await?;
+            sqlx::query(
+                "UPDATE todos SET list_id = $1, updated_at = now(), version = version + 1 WHERE list_id = $2 AND owner = $3",
+            )
+                .bind(move_to)
+                .bind(id)
+                .bind(owner)
+                .execute(&mut *transaction)
+                .await
+                .context("failed to move todos")?;
+        }
+        // also removed by the foreign key, deleted here to not depend on it
+        sqlx::query("DELETE FROM todos WHERE list_id = $1 AND owner = $2")
+            .bind(id)
+            .bind(owner)
+            .execute(&mut *transaction)
+            .await
+            .context("failed to delete todos of list")?;
+        let result = sqlx::query("DELETE FROM lists WHERE id = $1 AND owner = $2")
             .bind(id)
-            .execute(&self.pool)
+            .bind(owner)
+            .execute(&mut *transaction)
             .await
-            .context("failed to delete todo")?;
+            .context("failed to delete list")?;
         if result.rows_affected() == 0 {
             return Err(DatabaseError::NotFound { id });
         }
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
         Ok(())
     }
 
-    pub async fn update(&self, id: Uuid, todo: DbUpdateTodo) -> Result<DbTodo, DatabaseError> {
-        let row = sqlx::query_as::<_, DbTodo>(
-            "UPDATE todos SET text = COALESCE($1, text), completed = COALESCE($2, completed) WHERE id = $3 RETURNING id, text, completed"
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "insert_api_key", otel.kind = "client")
+    )]
+    async fn insert_api_key(
+        &self,
+        owner: &str,
+        key: DbNewApiKey,
+    ) -> Result<DbApiKey, DatabaseError> {
+        let _timer = QueryTimer::start("insert_api_key");
+        let mut connection = self.acquire().await?;
+        let api_key = sqlx::query_as::<_, DbApiKey>(
+            "INSERT INTO api_keys (id, owner, name, role, prefix, key_hash) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id, owner, name, role, prefix, created_at, revoked_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner)
+        .bind(key.name)
+        .bind(key.role)
+        .bind(key.prefix)
+        .bind(key.key_hash)
+        .fetch_one(&mut *connection)
+        .await
+        .context("failed to insert api key")?;
+        Ok(api_key)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "get_api_keys", otel.kind = "client")
+    )]
+    async fn get_api_keys(&self, owner: &str) -> Result<Vec<DbApiKey>, DatabaseError> {
+        let _timer = QueryTimer::start("get_api_keys");
+        let mut connection = self.acquire().await?;
+        let api_keys = sqlx::query_as::<_, DbApiKey>(
+            "SELECT id, owner, name, role, prefix, created_at, revoked_at FROM api_keys WHERE owner = $1 ORDER BY created_at, id",
+        )
+        .bind(owner)
+        .fetch_all(&mut *connection)
+        .await
+        .context("failed to get api keys")?;
+        Ok(api_keys)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "revoke_api_key", otel.kind = "client")
+    )]
+    async fn revoke_api_key(&self, owner: &str, id: Uuid) -> Result<DbApiKey, DatabaseError> {
+        let _timer = QueryTimer::start("revoke_api_key");
+        let mut connection = self.acquire().await?;
+        let api_key = sqlx::query_as::<_, DbApiKey>(
+            "UPDATE api_keys SET revoked_at = COALESCE(revoked_at, now()) WHERE id = $1 AND owner = $2 RETURNING id, owner, name, role, prefix, created_at, revoked_at",
         )
-        .bind(todo.text)
-        .bind(todo.completed)
         .bind(id)
-        .fetch_one(&self.pool)
+        .bind(owner)
+        .fetch_optional(&mut *connection)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => DatabaseError::NotFound { id },
-            e => anyhow::Error::from(e).context("failed to update todo").into(),
-        })?;
-        Ok(row)
+        .context("failed to revoke api key")?;
+        api_key.ok_or(DatabaseError::NotFound { id })
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "find_api_key", otel.kind = "client")
+    )]
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<DbApiKey>, DatabaseError> {
+        let _timer = QueryTimer::start("find_api_key");
+        let mut connection = self.acquire().await?;
+        let api_key = sqlx::query_as::<_, DbApiKey>(
+            "SELECT id, owner, name, role, prefix, created_at, revoked_at FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL",
+        )
+        .bind(key_hash)
+        .fetch_optional(&mut *connection)
+        .await
+        .context("failed to find api key")?;
+        Ok(api_key)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            db.system = "postgresql",
+            db.operation = "insert_idempotency_key",
+            otel.kind = "client"
+        )
+    )]
+    async fn insert_idempotency_key(
+        &self,
+        owner: &str,
+        key: DbNewIdempotencyKey,
+    ) -> Result<Option<DbIdempotencyKey>, DatabaseError> {
+        let _timer = QueryTimer::start("insert_idempotency_key");
+        let now = Utc::now().trunc_subsecs(6);
+        let mut connection = self.acquire().await?;
+        sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&mut *connection)
+            .await
+            .context("failed to remove expired idempotency keys")?;
+        // a concurrent request may have reserved the key, keys expired meanwhile are replaced
+        let inserted = sqlx::query(
+            "INSERT INTO idempotency_keys (owner, key, request_hash, created_at, expires_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (owner, key) DO UPDATE SET request_hash = excluded.request_hash, response_status = NULL, response_body = NULL, created_at = excluded.created_at, expires_at = excluded.expires_at WHERE idempotency_keys.expires_at <= excluded.created_at",
+        )
+        .bind(owner)
+        .bind(&key.key)
+        .bind(key.request_hash)
+        .bind(now)
+        .bind(key.expires_at.trunc_subsecs(6))
+        .execute(&mut *connection)
+        .await
+        .context("failed to insert idempotency key")?;
+        if inserted.rows_affected() > 0 {
+            return Ok(None);
+        }
+
+        let stored = sqlx::query_as::<_, DbIdempotencyKey>(
+            "SELECT owner, key, request_hash, response_status, response_body, created_at, expires_at FROM idempotency_keys WHERE owner = $1 AND key = $2",
+        )
+        .bind(owner)
+        .bind(&key.key)
+        .fetch_optional(&mut *connection)
+        .await
+        .context("failed to get idempotency key")?;
+        match stored {
+            Some(stored) => Ok(Some(stored)),
+            None => Err(anyhow::anyhow!("idempotency key was removed concurrently").into()),
+        }
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            db.system = "postgresql",
+            db.operation = "complete_idempotency_key",
+            otel.kind = "client"
+        )
+    )]
+    async fn complete_idempotency_key(
+        &self,
+        owner: &str,
+        key: &str,
+        response: DbIdempotentResponse,
+    ) -> Result<(), DatabaseError> {
+        let _timer = QueryTimer::start("complete_idempotency_key");
+        let mut connection = self.acquire().await?;
+        sqlx::query(
+            "UPDATE idempotency_keys SET response_status = $3, response_body = $4, expires_at = $5 WHERE owner = $1 AND key = $2",
+        )
+        .bind(owner)
+        .bind(key)
+        .bind(response.status)
+        .bind(response.body)
+        .bind(response.expires_at.trunc_subsecs(6))
+        .execute(&mut *connection)
+        .await
+        .context("failed to complete idempotency key")?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            db.system = "postgresql",
+            db.operation = "remove_idempotency_key",
+            otel.kind = "client"
+        )
+    )]
+    async fn remove_idempotency_key(&self, owner: &str, key: &str) -> Result<(), DatabaseError> {
+        let _timer = QueryTimer::start("remove_idempotency_key");
+        let mut connection = self.acquire().await?;
+        sqlx::query("DELETE FROM idempotency_keys WHERE owner = $1 AND key = $2")
+            .bind(owner)
+            .bind(key)
+            .execute(&mut *connection)
+            .await
+            .context("failed to remove idempotency key")?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(db.system = "postgresql", db.operation = "ping", otel.kind = "client")
+    )]
+    async fn ping(&self) -> Result<Option<DbPoolStatus>, DatabaseError> {
+        let _timer = QueryTimer::start("ping");
+        let status = self.pool_status();
+        let mut connection = self.acquire().await?;
+        sqlx::query("SELECT 1")
+            .execute(&mut *connection)
+            .await
+            .context("failed to ping database")?;
+        Ok(status)
+    }
+
+    fn pool_status(&self) -> Option<DbPoolStatus> {
+        Some(DbPoolStatus {
+            size: self.pool.size(),
+            idle: u32::try_from(self.pool.num_idle()).unwrap_or(u32::MAX),
+            max_size: self.pool.options().get_max_connections(),
+        })
+    }
+}
+
+async fn insert_todo(
+    connection: &mut PgConnection,
+    owner: &str,
+    todo: DbNewTodo,
+) -> Result<DbTodo, DatabaseError> {
+    if let Some(list_id) = todo.list_id {
+        check_list(&mut *connection, owner, list_id).await?;
+    }
+    let mut row = sqlx::query_as::<_, DbTodo>(
+        "INSERT INTO todos (id, text, completed, owner, list_id, due_at, priority) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(todo.text)
+    .bind(false) // default completed to false
+    .bind(owner)
+    .bind(todo.list_id)
+    .bind(todo.due_at.map(|due_at| due_at.trunc_subsecs(6)))
+    .bind(todo.priority)
+    .fetch_one(&mut *connection)
+    .await
+    .context("failed to insert todo")?;
+    if !todo.tags.is_empty() {
+        set_tags(&mut *connection, owner, row.id, &todo.tags).await?;
+        load_tags(connection, std::slice::from_mut(&mut row)).await?;
+    }
+    Ok(row)
+}
+
+async fn remove_todo(
+    connection: &mut PgConnection,
+    owner: &str,
+    id: Uuid,
+    expected_version: Option<i64>,
+) -> Result<(), DatabaseError> {
+    let result = sqlx::query(
+        "DELETE FROM todos WHERE id = $1 AND owner = $3 AND ($2::BIGINT IS NULL OR version = $2)",
+    )
+    .bind(id)
+    .bind(expected_version)
+    .bind(owner)
+    .execute(&mut *connection)
+    .await
+    .context("failed to delete todo")?;
+    if result.rows_affected() == 0 {
+        return Err(missing(connection, owner, id, expected_version).await);
+    }
+    Ok(())
+}
+
+async fn update_todo(
+    connection: &mut PgConnection,
+    owner: &str,
+    id: Uuid,
+    todo: DbUpdateTodo,
+    expected_version: Option<i64>,
+) -> Result<DbTodo, DatabaseError> {
+    if let Some(Some(list_id)) = todo.list_id {
+        check_list(&mut *connection, owner, list_id).await?;
+    }
+    let row = sqlx::query_as::<_, DbTodo>(
+        "UPDATE todos SET
+            text = COALESCE($1, text),
+            completed = COALESCE($2, completed),
+            completed_at = CASE WHEN $2 THEN COALESCE(completed_at, now()) WHEN NOT $2 THEN NULL ELSE completed_at END,
+            list_id = CASE WHEN $6 THEN $7 ELSE list_id END,
+            due_at = CASE WHEN $8 THEN $9 ELSE due_at END,
+            priority = COALESCE($10, priority),
+            updated_at = now(),
+            version = version + 1
+        WHERE id = $3 AND owner = $5 AND ($4::BIGINT IS NULL OR version = $4::BIGINT)
+        RETURNING id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority"
+    )
+    .bind(todo.text)
+    .bind(todo.completed)
+    .bind(id)
+    .bind(expected_version)
+    .bind(owner)
+    .bind(todo.list_id.is_some())
+    .bind(todo.list_id.flatten())
+    .bind(todo.due_at.is_some())
+    .bind(todo.due_at.flatten().map(|due_at| due_at.trunc_subsecs(6)))
+    .bind(todo.priority)
+    .fetch_optional(&mut *connection)
+    .await
+    .context("failed to update todo")?;
+    let Some(mut row) = row else {
+        return Err(missing(connection, owner, id, expected_version).await);
+    };
+    if let Some(tags) = todo.tags {
+        set_tags(&mut *connection, owner, id, &tags).await?;
+    }
+    load_tags(connection, std::slice::from_mut(&mut row)).await?;
+    Ok(row)
+}
+
+/// Replaces the tags of the todo, the owner's tags missing so far are created.
+async fn set_tags(
+    connection: &mut PgConnection,
+    owner: &str,
+    id: Uuid,
+    tags: &[String],
+) -> Result<(), DatabaseError> {
+    sqlx::query("DELETE FROM todo_tags WHERE todo_id = $1")
+        .bind(id)
+        .execute(&mut *connection)
+        .await
+        .context("failed to remove tags of todo")?;
+    if tags.is_empty() {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO tags (owner, name) SELECT $1, UNNEST($2::TEXT[]) ON CONFLICT (owner, name) DO NOTHING",
+    )
+    .bind(owner)
+    .bind(tags)
+    .execute(&mut *connection)
+    .await
+    .context("failed to insert tags")?;
+    sqlx::query(
+        "INSERT INTO todo_tags (todo_id, tag_id) SELECT $1, id FROM tags WHERE owner = $2 AND name = ANY($3)",
+    )
+    .bind(id)
+    .bind(owner)
+    .bind(tags)
+    .execute(&mut *connection)
+    .await
+    .context("failed to insert tags of todo")?;
+    Ok(())
+}
+
+/// Sets the tags of the todos in the byte order of the in-memory database.
+async fn load_tags(
+    connection: &mut PgConnection,
+    todos: &mut [DbTodo],
+) -> Result<(), DatabaseError> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<Uuid> = todos.iter().map(|todo| todo.id).collect();
+    let rows = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT todo_tags.todo_id, tags.name FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id = ANY($1) ORDER BY tags.name COLLATE \"C\"",
+    )
+    .bind(ids)
+    .fetch_all(connection)
+    .await
+    .context("failed to fetch tags")?;
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (id, name) in rows {
+        tags.entry(id).or_default().push(name);
+    }
+    for todo in todos {
+        todo.tags = tags.remove(&todo.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Lists of the owner with the counts of their todos, `$2` selects a single list.
+const SELECT_LISTS: &str = "SELECT lists.id, lists.owner, lists.name, lists.created_at, lists.updated_at, COUNT(todos.id) AS todo_count, COUNT(todos.id) FILTER (WHERE todos.completed) AS completed_count FROM lists LEFT JOIN todos ON todos.list_id = lists.id WHERE lists.owner = $1 AND ($2::UUID IS NULL OR lists.id = $2) GROUP BY lists.id ORDER BY lists.created_at, lists.id";
+
+async fn select_list(
+    connection: &mut PgConnection,
+    owner: &str,
+    id: Uuid,
+) -> Result<DbList, DatabaseError> {
+    let list = sqlx::query_as::<_, DbList>(SELECT_LISTS)
+        .bind(owner)
+        .bind(Some(id))
+        .fetch_optional(connection)
+        .await
+        .context("failed to get list")?;
+    list.ok_or(DatabaseError::NotFound { id })
+}
+
+/// Fails with `DatabaseError::ListNotFound` unless the owner has the list.
+async fn check_list(
+    connection: &mut PgConnection,
+    owner: &str,
+    id: Uuid,
+) -> Result<(), DatabaseError> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM lists WHERE id = $1 AND owner = $2)")
+            .bind(id)
+            .bind(owner)
+            .fetch_one(connection)
+            .await
+            .context("failed to check list")?;
+    if !exists {
+        return Err(DatabaseError::ListNotFound { id });
+    }
+    Ok(())
+}
+
+/// Error for a todo that was not changed, either missing or with another version.
+async fn missing(
+    connection: &mut PgConnection,
+    owner: &str,
+    id: Uuid,
+    expected_version: Option<i64>,
+) -> DatabaseError {
+    let Some(expected_version) = expected_version else {
+        return DatabaseError::NotFound { id };
+    };
+    let exists =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM todos WHERE id = $1 AND owner = $2)")
+            .bind(id)
+            .bind(owner)
+            .fetch_one(connection)
+            .await
+            .context("failed to check todo version");
+    match exists {
+        Ok(true) => DatabaseError::VersionMismatch {
+            id,
+            expected_version,
+        },
+        Ok(false) => DatabaseError::NotFound { id },
+        Err(e) => e.into(),
+    }
+}
+
+/// Column the list is ordered by. Text is compared with the "C" collation to match byte
+/// order of the in-memory database.
+fn sort_column(sort: DbTodosSort) -> &'static str {
+    match sort {
+        DbTodosSort::Id => "id",
+        DbTodosSort::Text | DbTodosSort::TextDesc => "text COLLATE \"C\"",
+        DbTodosSort::CreatedAt | DbTodosSort::CreatedAtDesc => "created_at",
+        DbTodosSort::UpdatedAt | DbTodosSort::UpdatedAtDesc => "updated_at",
+        // same expression as the todos_due_at_idx index, `NO_DUE_AT` for missing due dates
+        DbTodosSort::DueAt | DbTodosSort::DueAtDesc => "COALESCE(due_at, '9999-12-31T23:59:59Z')",
+        DbTodosSort::Priority | DbTodosSort::PriorityDesc => "priority",
+    }
+}
+
+/// Rows after the sort key of the previous page, ties are broken by id.
+fn push_after<'args, T>(
+    builder: &mut QueryBuilder<'args, Postgres>,
+    sort: DbTodosSort,
+    value: T,
+    id: Uuid,
+) where
+    T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+{
+    let comparison = if sort.is_descending() { "<" } else { ">" };
+    builder
+        .push(format_args!(
+            " AND ({}, id) {} (",
+            sort_column(sort),
+            comparison
+        ))
+        .push_bind(value)
+        .push(", ")
+        .push_bind(id)
+        .push(")");
+}
+
+fn push_time_range(builder: &mut QueryBuilder<'_, Postgres>, column: &str, range: DbTimeRange) {
+    if let Some(after) = range.after {
+        builder
+            .push(format_args!(" AND {column} > "))
+            .push_bind(after);
+    }
+    if let Some(before) = range.before {
+        builder
+            .push(format_args!(" AND {column} < "))
+            .push_bind(before);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlx::migrate::Migrator;
+    use opentelemetry::{trace::TracerProvider, KeyValue};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
     use testcontainers_modules::{
         postgres,
         testcontainers::{runners::AsyncRunner, ContainerAsync},
     };
-
-    const MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+    use tracing::Instrument;
+    use tracing_subscriber::prelude::*;
 
     async fn setup() -> (ContainerAsync<postgres::Postgres>, PostgresDB) {
         let postgres_container = postgres::Postgres::default().start().await.unwrap();
@@ -92,7 +988,7 @@ mod tests {
         let db = PostgresDB::new(connection_url.to_string(), 1)
             .await
             .unwrap();
-        MIGRATOR.run(&db.pool).await.unwrap();
+        db.migrate().await.unwrap();
 
         (postgres_container, db)
     }
@@ -101,14 +997,57 @@ mod tests {
         container.rm().await.unwrap();
     }
 
+    fn all_todos() -> DbTodosQuery {
+        DbTodosQuery {
+            limit: 100,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrate_twice() {
+        let (postgres_container, db) = setup().await;
+
+        db.migrate().await.unwrap();
+        db.check_schema_version().await.unwrap();
+
+        shutdown(postgres_container).await;
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_version_ahead() {
+        let (postgres_container, db) = setup().await;
+
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES ($1, 'from the future', TRUE, '', 0)",
+        )
+        .bind(99991231000000_i64)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let result = db.check_schema_version().await;
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("database schema version 99991231000000 is ahead"));
+
+        shutdown(postgres_container).await;
+    }
+
     #[tokio::test]
     async fn test_insert() {
         let (postgres_container, db) = setup().await;
 
         let new_todo = DbNewTodo {
             text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        let inserted_todo = db.insert(new_todo).await.unwrap();
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
         assert_eq!(inserted_todo.text, "Test todo");
         assert_eq!(inserted_todo.completed, false);
 
@@ -119,7 +1058,7 @@ mod tests {
     async fn test_get_values_empty() {
         let (postgres_container, db) = setup().await;
 
-        let todos = db.get_values().await.unwrap();
+        let todos = db.get_values("user", all_todos()).await.unwrap();
         assert_eq!(todos.len(), 0);
 
         shutdown(postgres_container).await;
@@ -131,10 +1070,14 @@ mod tests {
 
         let new_todo = DbNewTodo {
             text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        db.insert(new_todo).await.unwrap();
+        db.insert("user", new_todo).await.unwrap();
 
-        let todos = db.get_values().await.unwrap();
+        let todos = db.get_values("user", all_todos()).await.unwrap();
         assert_eq!(todos.len(), 1);
         assert_eq!(todos[0].text, "Test todo");
         assert_eq!(todos[0].completed, false);
@@ -148,14 +1091,25 @@ mod tests {
 
         let new_todo = DbNewTodo {
             text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        let inserted_todo = db.insert(new_todo).await.unwrap();
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
 
         let update_todo = DbUpdateTodo {
             text: Some("Updated todo".to_string()),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
-        let updated_todo = db.update(inserted_todo.id, update_todo).await.unwrap();
+        let updated_todo = db
+            .update("user", inserted_todo.id, update_todo, None)
+            .await
+            .unwrap();
         assert_eq!(updated_todo.text, "Updated todo");
         assert_eq!(updated_todo.completed, true);
 
@@ -171,8 +1125,12 @@ mod tests {
         let update_todo = DbUpdateTodo {
             text: Some("Updated todo".to_string()),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
-        let result = db.update(not_found_id, update_todo).await;
+        let result = db.update("user", not_found_id, update_todo, None).await;
         assert!(result.is_err());
         assert!(matches!(
             result.err().unwrap(),
@@ -188,11 +1146,15 @@ mod tests {
 
         let new_todo = DbNewTodo {
             text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
         };
-        let inserted_todo = db.insert(new_todo).await.unwrap();
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
 
-        db.remove(inserted_todo.id).await.unwrap();
-        let todos = db.get_values().await.unwrap();
+        db.remove("user", inserted_todo.id, None).await.unwrap();
+        let todos = db.get_values("user", all_todos()).await.unwrap();
         assert_eq!(todos.len(), 0);
 
         shutdown(postgres_container).await;
@@ -203,7 +1165,7 @@ mod tests {
         let (postgres_container, db) = setup().await;
 
         let not_found_id = Uuid::new_v4();
-        let result = db.remove(not_found_id).await;
+        let result = db.remove("user", not_found_id, None).await;
         assert!(result.is_err());
         assert!(matches!(
             result.err().unwrap(),
@@ -212,4 +1174,39 @@ mod tests {
 
         shutdown(postgres_container).await;
     }
+
+    #[tokio::test]
+    async fn test_query_span() {
+        let (postgres_container, db) = setup().await;
+
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        // the tests run on a single thread, so the subscriber sees the query of the test only
+        let guard = tracing::subscriber::set_default(subscriber);
+        db.get_values("user", all_todos())
+            .instrument(tracing::info_span!("http_request"))
+            .await
+            .unwrap();
+        drop(guard);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let span = |name: &str| spans.iter().find(|span| span.name == name).unwrap();
+        let (request_span, query_span) = (span("http_request"), span("get_values"));
+        assert_eq!(
+            query_span.parent_span_id,
+            request_span.span_context.span_id()
+        );
+        assert!(query_span
+            .attributes
+            .contains(&KeyValue::new("db.operation", "get_values")));
+        assert!(query_span
+            .attributes
+            .contains(&KeyValue::new("db.system", "postgresql")));
+
+        shutdown(postgres_container).await;
+    }
 }
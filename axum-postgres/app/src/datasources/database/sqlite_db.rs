@@ -0,0 +1,1099 @@
+use super::{
+    check_migrations_version,
+    models::{
+        DbApiKey, DbBatchOperation, DbBatchResult, DbIdempotencyKey, DbIdempotentResponse, DbList,
+        DbNewApiKey, DbNewIdempotencyKey, DbNewList, DbNewTodo, DbPoolStatus, DbTagsMatch,
+        DbTimeRange, DbTodo, DbTodosQuery, DbTodosSort, DbUpdateList, DbUpdateTodo, NO_DUE_AT,
+    },
+    text_search_terms, DatabaseError, TodoRepository,
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{SubsecRound, Utc};
+use sqlx::{
+    migrate::Migrator,
+    pool::PoolConnection,
+    sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions},
+    Encode, Pool, QueryBuilder, Sqlite, Type,
+};
+use std::{collections::HashMap, str::FromStr, time::Duration};
+use uuid::Uuid;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+pub struct SqliteDB {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteDB {
+    pub async fn new(connection_url: String, max_connections: u32) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(&connection_url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .acquire_timeout(Duration::from_secs(3))
+            .connect_with(options)
+            .await?;
+        Ok(SqliteDB { pool })
+    }
+
+    /// Fails if the database has migrations applied that this binary does not know about,
+    /// e.g. after a rollback to an older release.
+    pub async fn check_schema_version(&self) -> anyhow::Result<()> {
+        let has_migrations: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("failed to read database schema version")?;
+        if !has_migrations {
+            return Ok(());
+        }
+
+        let applied: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+            .fetch_one(&self.pool)
+            .await
+            .context("failed to read database schema version")?;
+        check_migrations_version(&MIGRATOR, applied)
+    }
+
+    pub async fn migrate(&self) -> anyhow::Result<()> {
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .context("failed to run database migrations")?;
+        Ok(())
+    }
+
+    async fn acquire(&self) -> Result<PoolConnection<Sqlite>, DatabaseError> {
+        let connection = self
+            .pool
+            .acquire()
+            .await
+            .context("failed to acquire connection")?;
+        Ok(connection)
+    }
+}
+
+#[async_trait]
+impl TodoRepository for SqliteDB {
+    async fn get_values(
+        &self,
+        owner: &str,
+        query: DbTodosQuery,
+    ) -> Result<Vec<DbTodo>, DatabaseError> {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "SELECT id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority FROM todos WHERE owner = ",
+        );
+        builder.push_bind(owner.to_string());
+        if let Some(list_id) = query.list_id {
+            builder.push(" AND list_id = ").push_bind(list_id);
+        }
+        if let Some(completed) = query.completed {
+            builder.push(" AND completed = ").push_bind(completed);
+        }
+        if let Some(search) = query.search {
+            let terms = text_search_terms(&search);
+            if terms.is_empty() {
+                builder.push(" AND FALSE");
+            } else {
+                // quoted terms are matched as words and combined with AND
+                let match_query = terms
+                    .iter()
+                    .map(|term| format!("\"{}\"", term))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                builder
+                    .push(" AND seq IN (SELECT rowid FROM todos_search WHERE todos_search MATCH ")
+                    .push_bind(match_query)
+                    .push(")");
+            }
+        }
+        if !query.tags.is_empty() {
+            let count = query.tags.len() as i64;
+            // the tags of the query and of a todo are unique, so a todo with all of them has
+            // one matching tag for each
+            builder.push(" AND (SELECT COUNT(*) FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id = todos.id AND tags.name IN (");
+            let mut names = builder.separated(", ");
+            for tag in query.tags {
+                names.push_bind(tag);
+            }
+            builder.push("))");
+            match query.tags_match {
+                DbTagsMatch::All => builder.push(" = ").push_bind(count),
+                DbTagsMatch::Any => builder.push(" > 0"),
+            };
+        }
+        push_time_range(&mut builder, "created_at", query.created_at);
+        push_time_range(&mut builder, "updated_at", query.updated_at);
+        push_time_range(&mut builder, "completed_at", query.completed_at);
+        push_time_range(&mut builder, "due_at", query.due_at);
+        if let Some(after) = query.after {
+            match query.sort {
+                DbTodosSort::Id => {
+                    builder.push(" AND id > ").push_bind(after.id);
+                }
+                DbTodosSort::Text | DbTodosSort::TextDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.text.unwrap_or_default(),
+                    after.id,
+                ),
+                DbTodosSort::CreatedAt | DbTodosSort::CreatedAtDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.created_at.unwrap_or_default(),
+                    after.id,
+                ),
+                DbTodosSort::UpdatedAt | DbTodosSort::UpdatedAtDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.updated_at.unwrap_or_default(),
+                    after.id,
+                ),
+                DbTodosSort::DueAt | DbTodosSort::DueAtDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.due_at.unwrap_or(NO_DUE_AT),
+                    after.id,
+                ),
+                DbTodosSort::Priority | DbTodosSort::PriorityDesc => push_after(
+                    &mut builder,
+                    query.sort,
+                    after.priority.unwrap_or_default(),
+                    after.id,
+                ),
+            }
+        }
+        match query.sort {
+            DbTodosSort::Id => builder.push(" ORDER BY id"),
+            sort if sort.is_descending() => builder.push(format_args!(
+                " ORDER BY {} DESC, id DESC",
+                sort_column(sort)
+            )),
+            sort => builder.push(format_args!(" ORDER BY {}, id", sort_column(sort))),
+        };
+        builder.push(" LIMIT ").push_bind(query.limit);
+
+        let mut connection = self.acquire().await?;
+        let mut rows = builder
+            .build_query_as::<DbTodo>()
+            .fetch_all(&mut *connection)
+            .await
+            .context("failed to fetch todos")?;
+        load_tags(&mut connection, &mut rows).await?;
+        Ok(rows)
+    }
+
+    async fn get(&self, owner: &str, id: Uuid) -> Result<DbTodo, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let row = sqlx::query_as::<_, DbTodo>(
+            "SELECT id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority FROM todos WHERE id = $1 AND owner = $2",
+        )
+        .bind(id)
+        .bind(owner)
+        .fetch_optional(&mut *connection)
+        .await
+        .context("failed to fetch todo")?;
+        let mut row = row.ok_or(DatabaseError::NotFound { id })?;
+        load_tags(&mut connection, std::slice::from_mut(&mut row)).await?;
+        Ok(row)
+    }
+
+    async fn insert(&self, owner: &str, todo: DbNewTodo) -> Result<DbTodo, DatabaseError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        let row = insert_todo(&mut transaction, owner, todo).await?;
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(row)
+    }
+
+    async fn remove(
+        &self,
+        owner: &str,
+        id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        let mut connection = self.acquire().await?;
+        remove_todo(&mut connection, owner, id, expected_version).await
+    }
+
+    async fn update(
+        &self,
+        owner: &str,
+        id: Uuid,
+        todo: DbUpdateTodo,
+        expected_version: Option<i64>,
+    ) -> Result<DbTodo, DatabaseError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        let row = update_todo(&mut transaction, owner, id, todo, expected_version).await?;
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(row)
+    }
+
+    async fn batch(
+        &self,
+        owner: &str,
+        operations: Vec<DbBatchOperation>,
+    ) -> Result<Vec<DbBatchResult>, DatabaseError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        let mut results = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.into_iter().enumerate() {
+            let result = match operation {
+                DbBatchOperation::Insert(todo) => insert_todo(&mut transaction, owner, todo)
+                    .await
+                    .map(DbBatchResult::Inserted),
+                DbBatchOperation::Update {
+                    id,
+                    todo,
+                    expected_version,
+                } => update_todo(&mut transaction, owner, id, todo, expected_version)
+                    .await
+                    .map(DbBatchResult::Updated),
+                DbBatchOperation::Remove {
+                    id,
+                    expected_version,
+                } => remove_todo(&mut transaction, owner, id, expected_version)
+                    .await
+                    .map(|_| DbBatchResult::Removed(id)),
+            };
+            // the transaction is rolled back when dropped
+            results.push(result.map_err(|e| e.in_batch(index))?);
+        }
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(results)
+    }
+
+    async fn purge(&self, owner: &str) -> Result<u64, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let result = sqlx::query("DELETE FROM todos WHERE owner = $1")
+            .bind(owner)
+            .execute(&mut *connection)
+            .await
+            .context("failed to purge todos")?;
+        Ok(result.rows_affected())
+    }
+
+    async fn count_unowned(&self) -> Result<u64, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM todos WHERE owner = ''")
+            .fetch_one(&mut *connection)
+            .await
+            .context("failed to count unowned todos")?;
+        Ok(count as u64)
+    }
+
+    async fn assign_unowned(&self, owner: &str) -> Result<u64, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let result = sqlx::query("UPDATE todos SET owner = $1 WHERE owner = ''")
+            .bind(owner)
+            .execute(&mut *connection)
+            .await
+            .context("failed to assign unowned todos")?;
+        Ok(result.rows_affected())
+    }
+
+    async fn get_lists(&self, owner: &str) -> Result<Vec<DbList>, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let lists = sqlx::query_as::<_, DbList>(SELECT_LISTS)
+            .bind(owner)
+            .bind(None::<Uuid>)
+            .fetch_all(&mut *connection)
+            .await
+            .context("failed to get lists")?;
+        Ok(lists)
+    }
+
+    async fn get_list(&self, owner: &str, id: Uuid) -> Result<DbList, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        select_list(&mut connection, owner, id).await
+    }
+
+    async fn insert_list(&self, owner: &str, list: DbNewList) -> Result<DbList, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let list = sqlx::query_as::<_, DbList>(
+            "INSERT INTO lists (id, owner, name, created_at, updated_at) VALUES ($1, $2, $3, $4, $4) RETURNING id, owner, name, created_at, updated_at, 0 AS todo_count, 0 AS completed_count",
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner)
+        .bind(list.name)
+        .bind(Utc::now().trunc_subsecs(6))
+        .fetch_one(&mut *connection)
+        .await
+        .context("failed to insert list")?;
+        Ok(list)
+    }
+
+    async fn update_list(
+        &self,
+        owner: &str,
+        id: Uuid,
+        list: DbUpdateList,
+    ) -> Result<DbList, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let result = sqlx::query(
+            "UPDATE lists SET name = COALESCE($1, name), updated_at = $4 WHERE id = $2 AND owner = $3",
+        )
+            .bind(list.name)
+            .bind(id)
+            .bind(owner)
+            .bind(Utc::now().trunc_subsecs(6))
+            .execute(&mut *connection)
+            .await
+            .context("failed to update list")?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound { id });
+        }
+        select_list(&mut connection, owner, id).await
+    }
+
+    async fn remove_list(
+        &self,
+        owner: &str,
+        id: Uuid,
+        move_to: Option<Uuid>,
+    ) -> Result<(), DatabaseError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .context("failed to begin transaction")?;
+        // the transaction is rolled back when dropped
+        if let Some(move_to) = move_to {
+            check_list(&mut transaction, owner, move_to).await?;
+            sqlx::query(
+                "UPDATE todos SET list_id = $1, updated_at = $4, version = version + 1 WHERE list_id = $2 AND owner = $3",
+            )
+                .bind(move_to)
+                .bind(id)
+                .bind(owner)
+                .bind(Utc::now().trunc_subsecs(6))
+                .execute(&mut *transaction)
+                .await
+                .context("failed to move todos")?;
+        }
+        // also removed by the foreign key, deleted here to not depend on it
+        sqlx::query("DELETE FROM todos WHERE list_id = $1 AND owner = $2")
+            .bind(id)
+            .bind(owner)
+            .execute(&mut *transaction)
+            .await
+            .context("failed to delete todos of list")?;
+        let result = sqlx::query("DELETE FROM lists WHERE id = $1 AND owner = $2")
+            .bind(id)
+            .bind(owner)
+            .execute(&mut *transaction)
+            .await
+            .context("failed to delete list")?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound { id });
+        }
+        transaction
+            .commit()
+            .await
+            .context("failed to commit transaction")?;
+        Ok(())
+    }
+
+    async fn insert_api_key(
+        &self,
+        owner: &str,
+        key: DbNewApiKey,
+    ) -> Result<DbApiKey, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let api_key = sqlx::query_as::<_, DbApiKey>(
+            "INSERT INTO api_keys (id, owner, name, role, prefix, key_hash, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id, owner, name, role, prefix, created_at, revoked_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner)
+        .bind(key.name)
+        .bind(key.role)
+        .bind(key.prefix)
+        .bind(key.key_hash)
+        .bind(Utc::now().trunc_subsecs(6))
+        .fetch_one(&mut *connection)
+        .await
+        .context("failed to insert api key")?;
+        Ok(api_key)
+    }
+
+    async fn get_api_keys(&self, owner: &str) -> Result<Vec<DbApiKey>, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let api_keys = sqlx::query_as::<_, DbApiKey>(
+            "SELECT id, owner, name, role, prefix, created_at, revoked_at FROM api_keys WHERE owner = $1 ORDER BY created_at, id",
+        )
+        .bind(owner)
+        .fetch_all(&mut *connection)
+        .await
+        .context("failed to get api keys")?;
+        Ok(api_keys)
+    }
+
+    async fn revoke_api_key(&self, owner: &str, id: Uuid) -> Result<DbApiKey, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let api_key = sqlx::query_as::<_, DbApiKey>(
+            "UPDATE api_keys SET revoked_at = COALESCE(revoked_at, $3) WHERE id = $1 AND owner = $2 RETURNING id, owner, name, role, prefix, created_at, revoked_at",
+        )
+        .bind(id)
+        .bind(owner)
+        .bind(Utc::now().trunc_subsecs(6))
+        .fetch_optional(&mut *connection)
+        .await
+        .context("failed to revoke api key")?;
+        api_key.ok_or(DatabaseError::NotFound { id })
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> Result<Option<DbApiKey>, DatabaseError> {
+        let mut connection = self.acquire().await?;
+        let api_key = sqlx::query_as::<_, DbApiKey>(
+            "SELECT id, owner, name, role, prefix, created_at, revoked_at FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL",
+        )
+        .bind(key_hash)
+        .fetch_optional(&mut *connection)
+        .await
+        .context("failed to find api key")?;
+        Ok(api_key)
+    }
+
+    async fn insert_idempotency_key(
+        &self,
+        owner: &str,
+        key: DbNewIdempotencyKey,
+    ) -> Result<Option<DbIdempotencyKey>, DatabaseError> {
+        let now = Utc::now().trunc_subsecs(6);
+        let mut connection = self.acquire().await?;
+        sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&mut *connection)
+            .await
+            .context("failed to remove expired idempotency keys")?;
+        // a concurrent request may have reserved the key, keys expired meanwhile are replaced
+        let inserted = sqlx::query(
+            "INSERT INTO idempotency_keys (owner, key, request_hash, created_at, expires_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (owner, key) DO UPDATE SET request_hash = excluded.request_hash, response_status = NULL, response_body = NULL, created_at = excluded.created_at, expires_at = excluded.expires_at WHERE idempotency_keys.expires_at <= excluded.created_at",
+        )
+        .bind(owner)
+        .bind(&key.key)
+        .bind(key.request_hash)
+        .bind(now)
+        .bind(key.expires_at.trunc_subsecs(6))
+        .execute(&mut *connection)
+        .await
+        .context("failed to insert idempotency key")?;
+        if inserted.rows_affected() > 0 {
+            return Ok(None);
+        }
+
+        let stored = sqlx::query_as::<_, DbIdempotencyKey>(
+            "SELECT owner, key, request_hash, response_status, response_body, created_at, expires_at FROM idempotency_keys WHERE owner = $1 AND key = $2",
+        )
+        .bind(owner)
+        .bind(&key.key)
+        .fetch_optional(&mut *connection)
+        .await
+        .context("failed to get idempotency key")?;
+        match stored {
+            Some(stored) => Ok(Some(stored)),
+            None => Err(anyhow::anyhow!("idempotency key was removed concurrently").into()),
+        }
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        owner: &str,
+        key: &str,
+        response: DbIdempotentResponse,
+    ) -> Result<(), DatabaseError> {
+        let mut connection = self.acquire().await?;
+        sqlx::query(
+            "UPDATE idempotency_keys SET response_status = $3, response_body = $4, expires_at = $5 WHERE owner = $1 AND key = $2",
+        )
+        .bind(owner)
+        .bind(key)
+        .bind(response.status)
+        .bind(response.body)
+        .bind(response.expires_at.trunc_subsecs(6))
+        .execute(&mut *connection)
+        .await
+        .context("failed to complete idempotency key")?;
+        Ok(())
+    }
+
+    async fn remove_idempotency_key(&self, owner: &str, key: &str) -> Result<(), DatabaseError> {
+        let mut connection = self.acquire().await?;
+        sqlx::query("DELETE FROM idempotency_keys WHERE owner = $1 AND key = $2")
+            .bind(owner)
+            .bind(key)
+            .execute(&mut *connection)
+            .await
+            .context("failed to remove idempotency key")?;
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<Option<DbPoolStatus>, DatabaseError> {
+        let status = self.pool_status();
+        let mut connection = self.acquire().await?;
+        sqlx::query("SELECT 1")
+            .execute(&mut *connection)
+            .await
+            .context("failed to ping database")?;
+        Ok(status)
+    }
+
+    fn pool_status(&self) -> Option<DbPoolStatus> {
+        Some(DbPoolStatus {
+            size: self.pool.size(),
+            idle: u32::try_from(self.pool.num_idle()).unwrap_or(u32::MAX),
+            max_size: self.pool.options().get_max_connections(),
+        })
+    }
+}
+
+async fn insert_todo(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    todo: DbNewTodo,
+) -> Result<DbTodo, DatabaseError> {
+    if let Some(list_id) = todo.list_id {
+        check_list(&mut *connection, owner, list_id).await?;
+    }
+    let mut row = sqlx::query_as::<_, DbTodo>(
+        "INSERT INTO todos (id, text, completed, created_at, updated_at, owner, list_id, due_at, priority) VALUES ($1, $2, $3, $4, $4, $5, $6, $7, $8) RETURNING id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(todo.text)
+    .bind(false) // default completed to false
+    // same precision as Postgres, stored as text sorting in time order
+    .bind(Utc::now().trunc_subsecs(6))
+    .bind(owner)
+    .bind(todo.list_id)
+    .bind(todo.due_at.map(|due_at| due_at.trunc_subsecs(6)))
+    .bind(todo.priority)
+    .fetch_one(&mut *connection)
+    .await
+    .context("failed to insert todo")?;
+    if !todo.tags.is_empty() {
+        set_tags(&mut *connection, owner, row.id, &todo.tags).await?;
+        load_tags(connection, std::slice::from_mut(&mut row)).await?;
+    }
+    Ok(row)
+}
+
+async fn remove_todo(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    id: Uuid,
+    expected_version: Option<i64>,
+) -> Result<(), DatabaseError> {
+    let result = sqlx::query(
+        "DELETE FROM todos WHERE id = $1 AND owner = $3 AND ($2 IS NULL OR version = $2)",
+    )
+    .bind(id)
+    .bind(expected_version)
+    .bind(owner)
+    .execute(&mut *connection)
+    .await
+    .context("failed to delete todo")?;
+    if result.rows_affected() == 0 {
+        return Err(missing(connection, owner, id, expected_version).await);
+    }
+    Ok(())
+}
+
+async fn update_todo(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    id: Uuid,
+    todo: DbUpdateTodo,
+    expected_version: Option<i64>,
+) -> Result<DbTodo, DatabaseError> {
+    if let Some(Some(list_id)) = todo.list_id {
+        check_list(&mut *connection, owner, list_id).await?;
+    }
+    let row = sqlx::query_as::<_, DbTodo>(
+        "UPDATE todos SET
+            text = COALESCE($1, text),
+            completed = COALESCE($2, completed),
+            completed_at = CASE WHEN $2 THEN COALESCE(completed_at, $4) WHEN NOT $2 THEN NULL ELSE completed_at END,
+            list_id = CASE WHEN $7 THEN $8 ELSE list_id END,
+            due_at = CASE WHEN $9 THEN $10 ELSE due_at END,
+            priority = COALESCE($11, priority),
+            updated_at = $4,
+            version = version + 1
+        WHERE id = $3 AND owner = $6 AND ($5 IS NULL OR version = $5)
+        RETURNING id, text, completed, created_at, updated_at, completed_at, version, owner, list_id, due_at, priority"
+    )
+    .bind(todo.text)
+    .bind(todo.completed)
+    .bind(id)
+    .bind(Utc::now().trunc_subsecs(6))
+    .bind(expected_version)
+    .bind(owner)
+    .bind(todo.list_id.is_some())
+    .bind(todo.list_id.flatten())
+    .bind(todo.due_at.is_some())
+    .bind(todo.due_at.flatten().map(|due_at| due_at.trunc_subsecs(6)))
+    .bind(todo.priority)
+    .fetch_optional(&mut *connection)
+    .await
+    .context("failed to update todo")?;
+    let Some(mut row) = row else {
+        return Err(missing(connection, owner, id, expected_version).await);
+    };
+    if let Some(tags) = todo.tags {
+        set_tags(&mut *connection, owner, id, &tags).await?;
+    }
+    load_tags(connection, std::slice::from_mut(&mut row)).await?;
+    Ok(row)
+}
+
+/// Replaces the tags of the todo, the owner's tags missing so far are created.
+async fn set_tags(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    id: Uuid,
+    tags: &[String],
+) -> Result<(), DatabaseError> {
+    sqlx::query("DELETE FROM todo_tags WHERE todo_id = $1")
+        .bind(id)
+        .execute(&mut *connection)
+        .await
+        .context("failed to remove tags of todo")?;
+    for tag in tags {
+        sqlx::query(
+            "INSERT INTO tags (owner, name) VALUES ($1, $2) ON CONFLICT (owner, name) DO NOTHING",
+        )
+        .bind(owner)
+        .bind(tag)
+        .execute(&mut *connection)
+        .await
+        .context("failed to insert tag")?;
+        sqlx::query(
+            "INSERT INTO todo_tags (todo_id, tag_id) SELECT $1, id FROM tags WHERE owner = $2 AND name = $3 ON CONFLICT DO NOTHING",
+        )
+        .bind(id)
+        .bind(owner)
+        .bind(tag)
+        .execute(&mut *connection)
+        .await
+        .context("failed to insert tag of todo")?;
+    }
+    Ok(())
+}
+
+/// Sets the tags of the todos, in byte order like the other databases.
+async fn load_tags(
+    connection: &mut SqliteConnection,
+    todos: &mut [DbTodo],
+) -> Result<(), DatabaseError> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+    let mut builder = QueryBuilder::<Sqlite>::new(
+        "SELECT todo_tags.todo_id, tags.name FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id WHERE todo_tags.todo_id IN (",
+    );
+    let mut ids = builder.separated(", ");
+    for todo in todos.iter() {
+        ids.push_bind(todo.id);
+    }
+    builder.push(") ORDER BY tags.name");
+    let rows = builder
+        .build_query_as::<(Uuid, String)>()
+        .fetch_all(connection)
+        .await
+        .context("failed to fetch tags")?;
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (id, name) in rows {
+        tags.entry(id).or_default().push(name);
+    }
+    for todo in todos {
+        todo.tags = tags.remove(&todo.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Lists of the owner with the counts of their todos, `$2` selects a single list.
+const SELECT_LISTS: &str = "SELECT lists.id, lists.owner, lists.name, lists.created_at, lists.updated_at, COUNT(todos.id) AS todo_count, COUNT(CASE WHEN todos.completed THEN 1 END) AS completed_count FROM lists LEFT JOIN todos ON todos.list_id = lists.id WHERE lists.owner = $1 AND ($2 IS NULL OR lists.id = $2) GROUP BY lists.id ORDER BY lists.created_at, lists.id";
+
+async fn select_list(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    id: Uuid,
+) -> Result<DbList, DatabaseError> {
+    let list = sqlx::query_as::<_, DbList>(SELECT_LISTS)
+        .bind(owner)
+        .bind(Some(id))
+        .fetch_optional(connection)
+        .await
+        .context("failed to get list")?;
+    list.ok_or(DatabaseError::NotFound { id })
+}
+
+/// Fails with `DatabaseError::ListNotFound` unless the owner has the list.
+async fn check_list(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    id: Uuid,
+) -> Result<(), DatabaseError> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM lists WHERE id = $1 AND owner = $2)")
+            .bind(id)
+            .bind(owner)
+            .fetch_one(connection)
+            .await
+            .context("failed to check list")?;
+    if !exists {
+        return Err(DatabaseError::ListNotFound { id });
+    }
+    Ok(())
+}
+
+/// Error for a todo that was not changed, either missing or with another version.
+async fn missing(
+    connection: &mut SqliteConnection,
+    owner: &str,
+    id: Uuid,
+    expected_version: Option<i64>,
+) -> DatabaseError {
+    let Some(expected_version) = expected_version else {
+        return DatabaseError::NotFound { id };
+    };
+    let exists =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM todos WHERE id = $1 AND owner = $2)")
+            .bind(id)
+            .bind(owner)
+            .fetch_one(connection)
+            .await
+            .context("failed to check todo version");
+    match exists {
+        Ok(true) => DatabaseError::VersionMismatch {
+            id,
+            expected_version,
+        },
+        Ok(false) => DatabaseError::NotFound { id },
+        Err(e) => e.into(),
+    }
+}
+
+/// Column the list is ordered by. Text uses the default BINARY collation, same byte order
+/// as the other databases, and timestamps are stored as text sorting in time order.
+fn sort_column(sort: DbTodosSort) -> &'static str {
+    match sort {
+        DbTodosSort::Id => "id",
+        DbTodosSort::Text | DbTodosSort::TextDesc => "text",
+        DbTodosSort::CreatedAt | DbTodosSort::CreatedAtDesc => "created_at",
+        DbTodosSort::UpdatedAt | DbTodosSort::UpdatedAtDesc => "updated_at",
+        // same expression as the todos_due_at_idx index, `NO_DUE_AT` as it is bound
+        DbTodosSort::DueAt | DbTodosSort::DueAtDesc => {
+            "COALESCE(due_at, '9999-12-31T23:59:59+00:00')"
+        }
+        DbTodosSort::Priority | DbTodosSort::PriorityDesc => "priority",
+    }
+}
+
+/// Rows after the sort key of the previous page, ties are broken by id.
+fn push_after<'args, T>(
+    builder: &mut QueryBuilder<'args, Sqlite>,
+    sort: DbTodosSort,
+    value: T,
+    id: Uuid,
+) where
+    T: 'args + Encode<'args, Sqlite> + Type<Sqlite>,
+{
+    let comparison = if sort.is_descending() { "<" } else { ">" };
+    builder
+        .push(format_args!(
+            " AND ({}, id) {} (",
+            sort_column(sort),
+            comparison
+        ))
+        .push_bind(value)
+        .push(", ")
+        .push_bind(id)
+        .push(")");
+}
+
+fn push_time_range(builder: &mut QueryBuilder<'_, Sqlite>, column: &str, range: DbTimeRange) {
+    if let Some(after) = range.after {
+        builder
+            .push(format_args!(" AND {column} > "))
+            .push_bind(after);
+    }
+    if let Some(before) = range.before {
+        builder
+            .push(format_args!(" AND {column} < "))
+            .push_bind(before);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> SqliteDB {
+        let db = SqliteDB::new("sqlite::memory:".to_string(), 1)
+            .await
+            .unwrap();
+        db.migrate().await.unwrap();
+        db
+    }
+
+    fn all_todos() -> DbTodosQuery {
+        DbTodosQuery {
+            limit: 100,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrate_twice() {
+        let db = setup().await;
+
+        db.migrate().await.unwrap();
+        db.check_schema_version().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_version_without_migrations() {
+        let db = SqliteDB::new("sqlite::memory:".to_string(), 1)
+            .await
+            .unwrap();
+
+        db.check_schema_version().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_version_ahead() {
+        let db = setup().await;
+
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES ($1, 'from the future', TRUE, X'', 0)",
+        )
+        .bind(99991231000000_i64)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let result = db.check_schema_version().await;
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("database schema version 99991231000000 is ahead"));
+    }
+
+    #[tokio::test]
+    async fn test_insert() {
+        let db = setup().await;
+
+        let new_todo = DbNewTodo {
+            text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
+        };
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
+        assert_eq!(inserted_todo.text, "Test todo");
+        assert_eq!(inserted_todo.completed, false);
+    }
+
+    #[tokio::test]
+    async fn test_get_values_empty() {
+        let db = setup().await;
+
+        let todos = db.get_values("user", all_todos()).await.unwrap();
+        assert_eq!(todos.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_values() {
+        let db = setup().await;
+
+        let new_todo = DbNewTodo {
+            text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
+        };
+        db.insert("user", new_todo).await.unwrap();
+
+        let todos = db.get_values("user", all_todos()).await.unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].text, "Test todo");
+        assert_eq!(todos[0].completed, false);
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let db = setup().await;
+
+        let new_todo = DbNewTodo {
+            text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
+        };
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
+
+        let update_todo = DbUpdateTodo {
+            text: Some("Updated todo".to_string()),
+            completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
+        };
+        let updated_todo = db
+            .update("user", inserted_todo.id, update_todo, None)
+            .await
+            .unwrap();
+        assert_eq!(updated_todo.text, "Updated todo");
+        assert_eq!(updated_todo.completed, true);
+    }
+
+    #[tokio::test]
+    async fn test_update_not_found() {
+        let db = setup().await;
+
+        let not_found_id = Uuid::new_v4();
+
+        let update_todo = DbUpdateTodo {
+            text: Some("Updated todo".to_string()),
+            completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
+        };
+        let result = db.update("user", not_found_id, update_todo, None).await;
+        assert!(result.is_err());
+        assert!(matches!(
+            result.err().unwrap(),
+            DatabaseError::NotFound { id: _not_found_id }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let db = setup().await;
+
+        let new_todo = DbNewTodo {
+            text: "Test todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: Default::default(),
+        };
+        let inserted_todo = db.insert("user", new_todo).await.unwrap();
+
+        db.remove("user", inserted_todo.id, None).await.unwrap();
+        let todos = db.get_values("user", all_todos()).await.unwrap();
+        assert_eq!(todos.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_after_vacuum() {
+        let db = setup().await;
+
+        let mut ids = Vec::new();
+        for text in ["Buy milk", "Walk the dog", "Buy bread"] {
+            let new_todo = DbNewTodo {
+                text: text.to_string(),
+                list_id: None,
+                tags: Vec::new(),
+                due_at: None,
+                priority: Default::default(),
+            };
+            ids.push(db.insert("user", new_todo).await.unwrap().id);
+        }
+        db.remove("user", ids[0], None).await.unwrap();
+        sqlx::query("VACUUM").execute(&db.pool).await.unwrap();
+
+        let query = DbTodosQuery {
+            search: Some("buy".to_string()),
+            ..all_todos()
+        };
+        let todos = db.get_values("user", query).await.unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].id, ids[2]);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_keeps_todos_without_seq() {
+        let db = SqliteDB::new("sqlite::memory:".to_string(), 1)
+            .await
+            .unwrap();
+        let previous = Migrator {
+            migrations: MIGRATOR
+                .migrations
+                .iter()
+                .filter(|migration| migration.version < 20250315090000)
+                .cloned()
+                .collect(),
+            ..Migrator::DEFAULT
+        };
+        previous.run(&db.pool).await.unwrap();
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO todos (id, text, completed, created_at, updated_at, owner) VALUES ($1, 'Buy milk', FALSE, '2025-01-01T00:00:00+00:00', '2025-01-01T00:00:00+00:00', 'user')",
+        )
+        .bind(id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO tags (owner, name) VALUES ('user', 'shopping')")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO todo_tags (todo_id, tag_id) SELECT $1, id FROM tags")
+            .bind(id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        db.migrate().await.unwrap();
+
+        let query = DbTodosQuery {
+            search: Some("milk".to_string()),
+            ..all_todos()
+        };
+        let todos = db.get_values("user", query).await.unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].id, id);
+        assert_eq!(todos[0].tags, vec!["shopping".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_remove_not_found() {
+        let db = setup().await;
+
+        let not_found_id = Uuid::new_v4();
+        let result = db.remove("user", not_found_id, None).await;
+        assert!(result.is_err());
+        assert!(matches!(
+            result.err().unwrap(),
+            DatabaseError::NotFound { id: _not_found_id }
+        ));
+    }
+}
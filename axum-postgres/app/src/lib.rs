@@ -0,0 +1,24 @@
+use datasources::database::TodoRepository;
+use server::{credentials::Credentials, rate_limit::RateLimiter, tokens::TokenKeys};
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
+
+pub mod datasources;
+pub mod server;
+
+#[cfg(test)]
+mod test_utils;
+
+pub struct AppState {
+    pub db: Arc<dyn TodoRepository>,
+    pub credentials: Credentials,
+    pub tokens: TokenKeys,
+    pub rate_limiter: RateLimiter,
+    /// How long responses of requests with an `Idempotency-Key` are replayed.
+    pub idempotency_ttl: Duration,
+    /// Set once a shutdown signal was received, readiness fails while requests are drained.
+    pub shutting_down: AtomicBool,
+}
+pub type SharedState = Arc<AppState>;
@@ -1,6 +1,14 @@
+use crate::config::LogLevel;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_sdk::trace::SdkTracerProvider;
 use tracing_subscriber::{prelude::*, Layer};
 
-pub fn init(log_level: String, use_json_format: bool) {
+/// Logs to stdout, spans are also exported with the tracer provider if OpenTelemetry is enabled.
+pub fn init(
+    log_level: LogLevel,
+    use_json_format: bool,
+    tracer_provider: Option<&SdkTracerProvider>,
+) {
     let mut layers = Vec::new();
     if use_json_format {
         let layer = tracing_subscriber::fmt::layer()
@@ -15,6 +23,10 @@ pub fn init(log_level: String, use_json_format: bool) {
         let layer = tracing_subscriber::fmt::layer().boxed();
         layers.push(layer);
     }
+    if let Some(provider) = tracer_provider {
+        let tracer = provider.tracer(env!("CARGO_CRATE_NAME"));
+        layers.push(tracing_opentelemetry::layer().with_tracer(tracer).boxed());
+    }
 
     tracing_subscriber::registry()
         .with(
@@ -1,38 +1,152 @@
+use axum_postgres::{
+    datasources::database::new_database,
+    server::{
+        credentials::hash_password,
+        metrics::{install_recorder, new_metrics_router},
+        rate_limit::RateLimiter,
+        routes::new_router,
+        tokens::TokenKeys,
+    },
+    AppState, SharedState,
+};
 use config::Config;
-use datasources::database::{new_database, Database};
-use server::routes::new_router;
-use std::sync::Arc;
+use std::{
+    env, io,
+    net::SocketAddr,
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::signal;
 
 mod config;
-mod datasources;
 mod logger;
-mod server;
-
-#[cfg(test)]
-mod test_utils;
-
-struct AppState {
-    db: Database,
-    credentials: Vec<(String, String)>,
-}
-type SharedState = Arc<AppState>;
+mod telemetry;
 
 #[tokio::main]
 async fn main() {
-    let config = Config::new();
+    // print the hash of a password read from stdin, for a CREDENTIALS entry of a new user
+    if env::args().nth(1).as_deref() == Some("hash-password") {
+        print_password_hash();
+        return;
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("invalid config: {}", error);
+            }
+            process::exit(1);
+        }
+    };
+    // only validate the config, e.g. before a deploy
+    if env::args().any(|arg| arg == "--check-config") {
+        println!("{:#?}", config);
+        println!("config is valid");
+        return;
+    }
+    // only apply database migrations and exit, e.g. as a deploy step
+    let migrate_only = env::args().any(|arg| arg == "--migrate-only");
+    if migrate_only && config.database_url.is_none() {
+        // the in-memory database has nothing to migrate, a deploy step must not succeed silently
+        eprintln!("--migrate-only requires DATABASE_URL");
+        process::exit(1);
+    }
+    // give the todos created before they had owners to a user and exit
+    let mut args = env::args().skip_while(|arg| arg != "--assign-owner");
+    let assign_owner = args.next().map(|_| args.next().unwrap_or_default());
+    if let Some(owner) = &assign_owner {
+        if config.database_url.is_none() {
+            eprintln!("--assign-owner requires DATABASE_URL");
+            process::exit(1);
+        }
+        if config.credentials.role(owner).is_none() {
+            eprintln!(
+                "--assign-owner requires a user of CREDENTIALS, got '{}'",
+                owner
+            );
+            process::exit(1);
+        }
+    }
 
-    logger::init(config.log_level, !config.environment.is_local());
+    // spans are only exported if a collector is configured
+    let tracer_provider = config.otlp_endpoint.as_deref().map(|endpoint| {
+        telemetry::init_tracer_provider(endpoint, config.otel_service_name.clone())
+    });
+    logger::init(
+        config.log_level,
+        !config.environment.is_local(),
+        tracer_provider.as_ref(),
+    );
+    tracing::debug!("config: {:?}", config);
+    let metrics_handle = install_recorder();
 
-    let db = new_database(config.database_url, config.database_max_connections)
-        .await
-        .expect("database initialization failed");
+    let db = new_database(
+        config.database_url,
+        config.database_max_connections,
+        config.database_run_migrations || migrate_only,
+    )
+    .await
+    .expect("database initialization failed");
+    if migrate_only {
+        tracing::info!("database migrations done");
+        return;
+    }
+    if let Some(owner) = assign_owner {
+        let assigned = db
+            .assign_unowned(&owner)
+            .await
+            .expect("failed to assign unowned todos");
+        tracing::info!("assigned {} unowned todos to {}", assigned, owner);
+        return;
+    }
+    match db.count_unowned().await {
+        Ok(0) => {}
+        Ok(unowned) => tracing::warn!(
+            "{} todos have no owner and are not visible to anyone, assign them with --assign-owner <user>",
+            unowned
+        ),
+        Err(e) => tracing::warn!("failed to count unowned todos: {}", e),
+    }
+    let tokens = match config.jwt_secret {
+        Some(secret) => TokenKeys::new(secret.as_bytes(), config.jwt_expiration),
+        None => {
+            tracing::warn!("JWT_SECRET is not set, access tokens are only valid until restart");
+            TokenKeys::random(config.jwt_expiration)
+        }
+    };
     let app_state = Arc::new(AppState {
         db,
         credentials: config.credentials,
+        tokens,
+        rate_limiter: RateLimiter::new(config.rate_limits),
+        idempotency_ttl: config.idempotency_ttl,
+        shutting_down: AtomicBool::new(false),
     });
 
-    let router = new_router(app_state);
+    let router = new_router(app_state.clone());
+    let metrics_router = new_metrics_router(app_state.clone(), metrics_handle);
+
+    // metrics are served on their own port, which is not exposed with the API
+    let metrics_listener =
+        tokio::net::TcpListener::bind(format!("0.0.0.0:{}", config.metrics_port))
+            .await
+            .expect("failed to bind to metrics port");
+    tracing::info!(
+        "listening for metrics on {}",
+        metrics_listener
+            .local_addr()
+            .expect("could not get local metrics address")
+    );
+    tokio::spawn(async move {
+        axum::serve(metrics_listener, metrics_router)
+            .await
+            .expect("metrics server failed");
+    });
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", config.port))
         .await
@@ -41,13 +155,46 @@ async fn main() {
         "listening on {}",
         listener.local_addr().expect("could not get local address")
     );
-    axum::serve(listener, router.into_make_service())
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .expect("server failed");
+    // the address of the client is the key of the rate limits of unauthenticated requests
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(app_state, config.shutdown_drain))
+    .await
+    .expect("server failed");
+
+    // exports the spans which are still buffered
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            tracing::error!("failed to shut down tracer provider: {}", e);
+        }
+    }
+}
+
+fn print_password_hash() {
+    let mut password = String::new();
+    if let Err(e) = io::stdin().read_line(&mut password) {
+        eprintln!("failed to read password: {}", e);
+        process::exit(1);
+    }
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        eprintln!("password must not be empty");
+        process::exit(1);
+    }
+    match hash_password(password) {
+        Ok(hash) => println!("{}", hash),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
 }
 
-async fn shutdown_signal() {
+/// Completes after a shutdown signal once the readiness has failed for `drain`, so load balancers
+/// stop sending requests before the server stops accepting them.
+async fn shutdown_signal(state: SharedState, drain: Duration) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -69,4 +216,8 @@ async fn shutdown_signal() {
         _ = ctrl_c => {},
         _ = terminate => {},
     }
+
+    state.shutting_down.store(true, Ordering::Relaxed);
+    tracing::info!("shutting down, draining requests for {:?}", drain);
+    tokio::time::sleep(drain).await;
 }
@@ -0,0 +1,59 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+
+/// Start of every key, to recognize keys e.g. in leaked secrets.
+const KEY_PREFIX: &str = "tdk_";
+/// Characters of a key shown in the list of keys.
+const SHOWN_LENGTH: usize = 12;
+
+/// New random API key, returned to the user once and only stored as its hash.
+pub struct GeneratedApiKey {
+    pub key: String,
+    pub prefix: String,
+    pub hash: String,
+}
+
+pub fn generate_api_key() -> GeneratedApiKey {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    let key = format!("{}{}", KEY_PREFIX, URL_SAFE_NO_PAD.encode(secret));
+    GeneratedApiKey {
+        prefix: key[..SHOWN_LENGTH].to_string(),
+        hash: hash_api_key(&key),
+        key,
+    }
+}
+
+/// Keys are random, so unlike passwords a fast unsalted hash is enough and lets keys be
+/// looked up by their hash.
+pub fn hash_api_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_api_key() {
+        let generated = generate_api_key();
+        assert!(generated.key.starts_with(KEY_PREFIX));
+        assert_eq!(generated.key.len(), KEY_PREFIX.len() + 43);
+        assert!(generated.key.starts_with(&generated.prefix));
+        assert_eq!(generated.prefix.len(), SHOWN_LENGTH);
+        assert_eq!(generated.hash, hash_api_key(&generated.key));
+        assert_ne!(generated.key, generate_api_key().key);
+    }
+
+    #[test]
+    fn test_hash_api_key() {
+        assert_eq!(
+            hash_api_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
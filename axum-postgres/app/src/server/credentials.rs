@@ -0,0 +1,229 @@
+use crate::server::roles::Role;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2, PasswordHash,
+};
+use std::{collections::HashMap, fmt};
+
+/// Password hashes and roles of the users allowed to authenticate, parsed from `user:hash` or
+/// `user:hash:role` entries, users without a role are editors. Hashes are Argon2 PHC strings,
+/// as created by `hash_password`, or bcrypt hashes.
+#[derive(Debug, Default, Clone)]
+pub struct Credentials {
+    users: HashMap<String, Credential>,
+}
+
+#[derive(Clone)]
+struct Credential {
+    hash: String,
+    role: Role,
+}
+
+// the hashes are kept out of logs, they allow offline guessing of the passwords
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credential")
+            .field("hash", &"[redacted]")
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Parses entries separated by whitespace or new lines, lines starting with `#` are ignored.
+    pub fn parse(entries: &str) -> Result<Self, String> {
+        let mut users = HashMap::new();
+        let entries = entries
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace);
+        for entry in entries {
+            let Some((user, hash)) = entry.split_once(':') else {
+                return Err(format!("credential is not user:hash: {}", entry));
+            };
+            // neither Argon2 nor bcrypt hashes contain a colon
+            let (hash, role) = match hash.split_once(':') {
+                Some((hash, role)) => (
+                    hash,
+                    role.parse()
+                        .map_err(|e| format!("credential of user {}: {}", user, e))?,
+                ),
+                None => (hash, Role::default()),
+            };
+            if user.is_empty() {
+                return Err("credential has an empty user".to_string());
+            }
+            if !is_password_hash(hash) {
+                return Err(format!(
+                    "credential of user {} is not an Argon2 or bcrypt hash",
+                    user
+                ));
+            }
+            let credential = Credential {
+                hash: hash.to_string(),
+                role,
+            };
+            users.insert(user.to_string(), credential);
+        }
+        Ok(Credentials { users })
+    }
+
+    /// Adds the credentials of `other`, replacing the hashes and roles of users in both.
+    pub fn extend(&mut self, other: Credentials) {
+        self.users.extend(other.users);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    /// Role of a configured user, without checking a password.
+    pub fn role(&self, user: &str) -> Option<Role> {
+        self.users.get(user).map(|credential| credential.role)
+    }
+
+    /// Checks the password against the hash of the user in constant time and returns the role
+    /// of the user if it matches. Unknown users are checked against another hash, so that the
+    /// response time does not reveal existing users.
+    pub fn verify(&self, user: &str, password: &str) -> Option<Role> {
+        match self.users.get(user) {
+            Some(credential) => {
+                verify_password(password, &credential.hash).then_some(credential.role)
+            }
+            None => {
+                if let Some(credential) = self.users.values().next() {
+                    verify_password(password, &credential.hash);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Hashes the password with Argon2id and a random salt, for a `user:hash` credential.
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("failed to hash password: {}", e))
+}
+
+fn is_password_hash(hash: &str) -> bool {
+    if is_bcrypt(hash) {
+        return hash.parse::<bcrypt::HashParts>().is_ok();
+    }
+    PasswordHash::new(hash).is_ok_and(|hash| hash.algorithm.as_str().starts_with("argon2"))
+}
+
+fn is_bcrypt(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    if is_bcrypt(hash) {
+        return bcrypt::verify(password, hash).unwrap_or(false);
+    }
+    PasswordHash::new(hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cheap parameters to keep the tests fast, the parameters are read from the hash
+    const ARGON2_PASS: &str =
+        "$argon2id$v=19$m=8,t=1,p=1$c2FsdHNhbHRzYWx0$wH39oZXzTjsLQboJAaXtb53+uphmEb9evfHyY9E7eMs";
+    const BCRYPT_PASS: &str = "$2b$04$driJ439QcPI8nc6H4iHNjO7PIHf70yjjL0aFiT0c9xlbrwaGiayxO";
+
+    #[test]
+    fn test_debug_redacts_hashes() {
+        let credentials = Credentials::parse(&format!("user:{}:viewer", ARGON2_PASS)).unwrap();
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("user"));
+        assert!(debug.contains("Viewer"));
+        assert!(!debug.contains(ARGON2_PASS));
+    }
+
+    #[test]
+    fn test_hash_password() {
+        let hash = hash_password("secret").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("secret", &hash));
+        assert!(!verify_password("other", &hash));
+        assert_ne!(hash, hash_password("secret").unwrap());
+    }
+
+    #[test]
+    fn test_credentials_verify() {
+        let credentials =
+            Credentials::parse(&format!("argon:{} bcrypt:{}", ARGON2_PASS, BCRYPT_PASS)).unwrap();
+
+        assert!(credentials.verify("argon", "pass").is_some());
+        assert!(credentials.verify("bcrypt", "pass").is_some());
+        assert!(credentials.verify("argon", "invalid").is_none());
+        assert!(credentials.verify("bcrypt", "invalid").is_none());
+        assert!(credentials.verify("unknown", "pass").is_none());
+        assert!(credentials.verify("", "").is_none());
+        assert!(Credentials::default().verify("argon", "pass").is_none());
+    }
+
+    #[test]
+    fn test_credentials_parse() {
+        let file = format!(
+            "# users\nargon:{}\n\n  bcrypt:{}\n# removed:{}\n",
+            ARGON2_PASS, BCRYPT_PASS, BCRYPT_PASS
+        );
+        let credentials = Credentials::parse(&file).unwrap();
+        assert_eq!(credentials.users.len(), 2);
+        assert!(credentials.verify("bcrypt", "pass").is_some());
+        assert!(Credentials::parse("").unwrap().is_empty());
+
+        assert_eq!(
+            Credentials::parse("user:pass").unwrap_err(),
+            "credential of user user is not an Argon2 or bcrypt hash"
+        );
+        assert_eq!(
+            Credentials::parse("user").unwrap_err(),
+            "credential is not user:hash: user"
+        );
+        assert!(Credentials::parse(&format!(":{}", ARGON2_PASS)).is_err());
+        assert!(Credentials::parse("user:$2b$04$invalid").is_err());
+    }
+
+    #[test]
+    fn test_credentials_roles() {
+        let credentials = Credentials::parse(&format!(
+            "admin:{}:admin viewer:{}:viewer editor:{}",
+            ARGON2_PASS, BCRYPT_PASS, ARGON2_PASS
+        ))
+        .unwrap();
+        assert_eq!(credentials.verify("admin", "pass"), Some(Role::Admin));
+        assert_eq!(credentials.verify("viewer", "pass"), Some(Role::Viewer));
+        assert_eq!(credentials.verify("editor", "pass"), Some(Role::Editor));
+        assert_eq!(credentials.verify("admin", "invalid"), None);
+        assert_eq!(credentials.role("viewer"), Some(Role::Viewer));
+        assert_eq!(credentials.role("unknown"), None);
+
+        assert_eq!(
+            Credentials::parse(&format!("user:{}:root", ARGON2_PASS)).unwrap_err(),
+            "credential of user user: role must be viewer, editor or admin: root"
+        );
+        assert!(Credentials::parse(&format!("user:{}:", ARGON2_PASS)).is_err());
+    }
+
+    #[test]
+    fn test_credentials_extend() {
+        let mut credentials = Credentials::parse(&format!("user:{}", ARGON2_PASS)).unwrap();
+        let argon2_other = hash_password("other").unwrap();
+        credentials.extend(Credentials::parse(&format!("user:{}", argon2_other)).unwrap());
+        assert!(credentials.verify("user", "other").is_some());
+        assert!(credentials.verify("user", "pass").is_none());
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct PurgeResponse {
+    /// Number of todos removed.
+    #[schema(example = 12)]
+    pub removed: u64,
+}
@@ -0,0 +1,60 @@
+use crate::{datasources::database::models::DbApiKey, server::roles::Role};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct NewApiKey {
+    /// Describes what the key is used for.
+    #[schema(example = "Nightly import")]
+    #[validate(length(min = 1, max = 100, message = "length must be between 1 and 100"))]
+    pub name: String,
+    /// Role the key authenticates with, defaults to the role of the user and cannot be higher.
+    pub role: Option<Role>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ApiKey {
+    #[schema(example = "839b56dc-42cb-4dd2-8390-6f2c628d52dd")]
+    pub id: String,
+    #[schema(example = "Nightly import")]
+    pub name: String,
+    pub role: Role,
+    /// Start of the key.
+    #[schema(example = "tdk_Xq3u9ZbA")]
+    pub prefix: String,
+    pub created_at: DateTime<Utc>,
+    /// Time the key was revoked, revoked keys are not accepted anymore.
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<DbApiKey> for ApiKey {
+    type Error = anyhow::Error;
+
+    fn try_from(db_api_key: DbApiKey) -> Result<Self, Self::Error> {
+        Ok(ApiKey {
+            id: db_api_key.id.to_string(),
+            name: db_api_key.name,
+            role: db_api_key.role.parse().map_err(anyhow::Error::msg)?,
+            prefix: db_api_key.prefix,
+            created_at: db_api_key.created_at,
+            revoked_at: db_api_key.revoked_at,
+        })
+    }
+}
+
+/// Created key, the only response that has the key itself.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct CreatedApiKey {
+    /// Send as `X-API-Key` header, it cannot be retrieved again.
+    #[schema(example = "tdk_Xq3u9ZbAd1uN0yL8vR2pW5kT7mH4sJ6cF9gE3aB0xQz")]
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ApiKeysResponse {
+    pub api_keys: Vec<ApiKey>,
+}
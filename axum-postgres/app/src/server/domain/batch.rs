@@ -0,0 +1,122 @@
+use crate::server::domain::todos::{NewTodo, Todo, UpdateTodo};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationErrors};
+
+/// Changes applied together, either all of them or none.
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct BatchRequest {
+    /// Applied in the given order, a later operation sees the changes of the earlier ones.
+    #[validate(
+        length(min = 1, max = 100, message = "must have between 1 and 100 operations"),
+        nested
+    )]
+    pub operations: Vec<BatchOperation>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Create(NewTodo),
+    Update(BatchUpdateTodo),
+    Delete(BatchDeleteTodo),
+}
+
+impl Validate for BatchOperation {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        match self {
+            BatchOperation::Create(new_todo) => new_todo.validate(),
+            BatchOperation::Update(update_todo) => update_todo.validate(),
+            BatchOperation::Delete(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct BatchUpdateTodo {
+    #[schema(example = "839b56dc-42cb-4dd2-8390-6f2c628d52dd")]
+    pub id: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub todo: UpdateTodo,
+    /// Update only if the todo still has this version, like `If-Match`.
+    pub version: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BatchDeleteTodo {
+    #[schema(example = "839b56dc-42cb-4dd2-8390-6f2c628d52dd")]
+    pub id: String,
+    /// Delete only if the todo still has this version, like `If-Match`.
+    pub version: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BatchResponse {
+    /// One result for each operation, in the order of the request.
+    pub results: Vec<BatchResult>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BatchResult {
+    /// Status the operation would have as a single request, 424 for operations that were
+    /// rolled back because another operation failed.
+    #[schema(example = 200)]
+    pub status: u16,
+    /// Id of the updated or deleted todo.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The created or updated todo.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub todo: Option<Todo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_batch_request_validation() {
+        let request: BatchRequest = serde_json::from_value(json!({
+            "operations": [
+                { "op": "create", "text": "New todo" },
+                { "op": "update", "id": "839b56dc-42cb-4dd2-8390-6f2c628d52dd", "completed": true },
+                { "op": "delete", "id": "839b56dc-42cb-4dd2-8390-6f2c628d52dd", "version": 2 },
+            ]
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+
+        let empty = BatchRequest { operations: vec![] };
+        let error = empty.validate().unwrap_err().to_string();
+        assert!(error.contains("must have between 1 and 100 operations"));
+
+        let too_many = BatchRequest {
+            operations: (0..101)
+                .map(|_| {
+                    BatchOperation::Create(NewTodo {
+                        text: "New todo".to_string(),
+                        list_id: None,
+                        tags: Vec::new(),
+                        due_at: None,
+                        priority: None,
+                    })
+                })
+                .collect(),
+        };
+        assert!(too_many.validate().is_err());
+
+        let invalid_text: BatchRequest = serde_json::from_value(json!({
+            "operations": [
+                { "op": "create", "text": "New todo" },
+                { "op": "update", "id": "839b56dc-42cb-4dd2-8390-6f2c628d52dd", "text": "" },
+            ]
+        }))
+        .unwrap();
+        let error = invalid_text.validate().unwrap_err().to_string();
+        assert!(error.contains("length must be between 1 and 200"));
+    }
+}
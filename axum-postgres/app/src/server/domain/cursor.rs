@@ -0,0 +1,72 @@
+use crate::server::domain::todos::Priority;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Opaque position in the Todo list, handed to clients as `next_cursor`.
+/// Holds the id of the last item and its sort key when sorted by other than id.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Cursor {
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor {
+            id: Uuid::new_v4(),
+            text: None,
+            created_at: None,
+            updated_at: None,
+            due_at: None,
+            priority: None,
+        };
+        let encoded = cursor.encode();
+        assert_eq!(Cursor::decode(&encoded), Some(cursor));
+    }
+
+    #[test]
+    fn test_cursor_roundtrip_with_sort_key() {
+        let cursor = Cursor {
+            id: Uuid::new_v4(),
+            text: Some("Buy groceries".to_string()),
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            due_at: Some(Utc::now()),
+            priority: Some(Priority::High),
+        };
+        let encoded = cursor.encode();
+        assert_eq!(Cursor::decode(&encoded), Some(cursor));
+    }
+
+    #[test]
+    fn test_cursor_decode_invalid() {
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("not-json")), None);
+    }
+}
@@ -6,3 +6,38 @@ pub struct ErrorResponse {
     #[schema(example = "internal error")]
     pub error: String,
 }
+
+/// Error as `application/problem+json` (RFC 7807), returned instead of `ErrorResponse` to
+/// requests accepting it.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// Always `about:blank`, the problem is described by the status.
+    #[serde(rename = "type")]
+    #[schema(example = "about:blank")]
+    pub problem_type: String,
+    #[schema(example = "Bad Request")]
+    pub title: String,
+    #[schema(example = 400)]
+    pub status: u16,
+    #[schema(example = "text: length must be between 1 and 200")]
+    pub detail: String,
+    /// Id of the request from the `x-request-id` header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "4f5d6c8e-6c3b-4a53-9f2c-0d1f3b8e5a7c")]
+    pub instance: Option<String>,
+    /// The invalid fields of a request that failed validation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+/// Validation error of a field, nested fields are separated by `.` and items of lists have
+/// their index like `operations[0].text`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct FieldError {
+    #[schema(example = "text")]
+    pub field: String,
+    #[schema(example = "length")]
+    pub code: String,
+    #[schema(example = "length must be between 1 and 200")]
+    pub message: String,
+}
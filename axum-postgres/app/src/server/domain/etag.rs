@@ -0,0 +1,90 @@
+use crate::server::domain::todos::TodosResponse;
+use axum_extra::headers::ETag;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+
+/// Strong entity tag of a todo, its quoted version.
+pub fn todo_etag(version: i64) -> ETag {
+    format!("\"{}\"", version)
+        .parse()
+        .expect("quoted number is a valid entity tag")
+}
+
+/// Version of a todo from its entity tag, weak tags never match a version.
+pub fn parse_todo_etag(etag: &str) -> Option<i64> {
+    etag.strip_prefix('"')?.strip_suffix('"')?.parse().ok()
+}
+
+/// Entity tag of a page of the Todo list, changes when an item of the page is added,
+/// updated or removed.
+pub fn todos_etag(response: &TodosResponse) -> ETag {
+    let mut hasher = Sha256::new();
+    for todo in &response.todos {
+        hasher.update(todo.id.as_bytes());
+        hasher.update(todo.version.to_be_bytes());
+    }
+    if let Some(next_cursor) = &response.next_cursor {
+        hasher.update(next_cursor.as_bytes());
+    }
+    let digest = hasher.finalize();
+    format!("\"{}\"", URL_SAFE_NO_PAD.encode(&digest[..16]))
+        .parse()
+        .expect("base64 is a valid entity tag")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::domain::todos::Todo;
+    use chrono::Utc;
+
+    fn response(versions: &[i64]) -> TodosResponse {
+        let todos = versions
+            .iter()
+            .enumerate()
+            .map(|(i, version)| Todo {
+                id: format!("00000000-0000-0000-0000-00000000000{}", i),
+                text: "test".to_string(),
+                completed: false,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                completed_at: None,
+                version: *version,
+                list_id: None,
+                tags: Vec::new(),
+                due_at: None,
+                priority: Default::default(),
+            })
+            .collect();
+        TodosResponse {
+            todos,
+            next_cursor: None,
+        }
+    }
+
+    #[test]
+    fn test_todo_etag_roundtrip() {
+        assert_eq!(todo_etag(42), "\"42\"".parse::<ETag>().unwrap());
+        assert_eq!(parse_todo_etag("\"42\""), Some(42));
+    }
+
+    #[test]
+    fn test_parse_todo_etag_invalid() {
+        assert_eq!(parse_todo_etag("42"), None);
+        assert_eq!(parse_todo_etag("W/\"42\""), None);
+        assert_eq!(parse_todo_etag("\"abc\""), None);
+    }
+
+    #[test]
+    fn test_todos_etag() {
+        assert_eq!(
+            todos_etag(&response(&[1, 2])),
+            todos_etag(&response(&[1, 2]))
+        );
+        assert_ne!(
+            todos_etag(&response(&[1, 2])),
+            todos_etag(&response(&[1, 3]))
+        );
+        assert_ne!(todos_etag(&response(&[1, 2])), todos_etag(&response(&[1])));
+    }
+}
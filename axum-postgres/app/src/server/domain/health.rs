@@ -0,0 +1,59 @@
+use crate::datasources::database::models::DbPoolStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct HealthResponse {
+    /// `ok`, or `unavailable` if the instance should not get requests.
+    #[schema(example = "ok")]
+    pub status: String,
+    /// Why the instance is unavailable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = json!(null))]
+    pub reason: Option<String>,
+    /// Connections of the database, unless the database has no connection pool.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<PoolHealth>,
+}
+
+impl HealthResponse {
+    pub fn ok(pool: Option<PoolHealth>) -> Self {
+        HealthResponse {
+            status: "ok".to_string(),
+            reason: None,
+            pool,
+        }
+    }
+
+    pub fn unavailable(reason: &str) -> Self {
+        HealthResponse {
+            status: "unavailable".to_string(),
+            reason: Some(reason.to_string()),
+            pool: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct PoolHealth {
+    /// Open connections, idle or in use.
+    #[schema(example = 3)]
+    pub size: u32,
+    #[schema(example = 2)]
+    pub idle: u32,
+    #[schema(example = 5)]
+    pub max_size: u32,
+    /// Every connection is open and in use, requests wait for a connection.
+    pub saturated: bool,
+}
+
+impl From<DbPoolStatus> for PoolHealth {
+    fn from(status: DbPoolStatus) -> Self {
+        PoolHealth {
+            size: status.size,
+            idle: status.idle,
+            max_size: status.max_size,
+            saturated: status.idle == 0 && status.size >= status.max_size,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::datasources::database::models::DbList;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+
+/// List of Todo items with the counts of its items.
+#[derive(Debug, Deserialize, Serialize, ToSchema, Clone)]
+pub struct List {
+    #[schema(example = "5f0c7a3e-8b1d-4c2a-9e6f-1d2b3c4d5e6f")]
+    pub id: String,
+    #[schema(example = "Groceries")]
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Items of the list.
+    #[schema(example = 3)]
+    pub todo_count: i64,
+    /// Completed items of the list.
+    #[schema(example = 1)]
+    pub completed_count: i64,
+}
+
+impl From<DbList> for List {
+    fn from(db_list: DbList) -> Self {
+        List {
+            id: db_list.id.to_string(),
+            name: db_list.name,
+            created_at: db_list.created_at,
+            updated_at: db_list.updated_at,
+            todo_count: db_list.todo_count,
+            completed_count: db_list.completed_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ListsResponse {
+    pub lists: Vec<List>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct NewList {
+    #[schema(example = "Groceries")]
+    #[validate(length(min = 1, max = 200, message = "length must be between 1 and 200"))]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct UpdateList {
+    #[schema(example = "Groceries")]
+    #[validate(length(min = 1, max = 200, message = "length must be between 1 and 200"))]
+    pub name: Option<String>,
+}
+
+/// What happens to the Todo items of a deleted list.
+#[derive(Debug, Default, Deserialize, Serialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteListQuery {
+    /// Id of a list to move the items to, the items are deleted with the list if missing.
+    #[param(value_type = Option<String>, example = "839b56dc-42cb-4dd2-8390-6f2c628d52dd")]
+    pub move_to: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_validation() {
+        let valid_list = NewList {
+            name: "Groceries".to_string(),
+        };
+        assert!(valid_list.validate().is_ok());
+
+        let empty_list = NewList {
+            name: "".to_string(),
+        };
+        let error = empty_list.validate().unwrap_err().to_string();
+        assert!(error.contains("length must be between 1 and 200"));
+
+        let long_name = UpdateList {
+            name: Some("a".repeat(201)),
+        };
+        let error = long_name.validate().unwrap_err().to_string();
+        assert!(error.contains("length must be between 1 and 200"));
+
+        let no_name = UpdateList { name: None };
+        assert!(no_name.validate().is_ok());
+    }
+}
@@ -1,3 +1,11 @@
+pub mod admin;
+pub mod api_keys;
+pub mod auth;
+pub mod batch;
 pub mod common;
+pub mod cursor;
 pub mod errors;
+pub mod etag;
+pub mod health;
+pub mod lists;
 pub mod todos;
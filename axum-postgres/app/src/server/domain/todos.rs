@@ -1,11 +1,184 @@
-use crate::datasources::database::models::DbTodo;
+use crate::datasources::database::models::{
+    DbPriority, DbTagsMatch, DbTimeRange, DbTodo, DbTodosSort, NO_DUE_AT,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
-use validator::Validate;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+pub const DEFAULT_PAGE_LIMIT: u32 = 50;
+pub const MAX_TAGS: usize = 10;
 
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct TodosResponse {
     pub todos: Vec<Todo>,
+    /// Cursor to pass as `cursor` to fetch the next page, missing on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "ODM5YjU2ZGMtNDJjYi00ZGQyLTgzOTAtNmYyYzYyOGQ1MmRk")]
+    pub next_cursor: Option<String>,
+}
+
+/// Filtering, sorting and pagination of the Todo list.
+#[derive(Debug, Default, Deserialize, Serialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TodosQuery {
+    /// Maximum number of items to return, defaults to 50.
+    #[param(minimum = 1, maximum = 100, example = 20)]
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub limit: Option<u32>,
+    /// Cursor returned as `next_cursor` by the previous page, used with the same filters and sort.
+    pub cursor: Option<String>,
+    /// Return only completed or not completed items.
+    pub completed: Option<bool>,
+    /// Full-text search, items must contain all the given words.
+    #[param(example = "groceries")]
+    #[validate(length(min = 1, max = 200, message = "length must be between 1 and 200"))]
+    pub q: Option<String>,
+    /// Sort order, defaults to id.
+    pub sort: Option<TodosSort>,
+    /// Return only items created after this RFC 3339 time.
+    #[param(example = "2025-01-01T00:00:00Z")]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Return only items created before this RFC 3339 time.
+    pub created_before: Option<DateTime<Utc>>,
+    /// Return only items last updated after this RFC 3339 time.
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Return only items last updated before this RFC 3339 time.
+    pub updated_before: Option<DateTime<Utc>>,
+    /// Return only items completed after this RFC 3339 time.
+    pub completed_after: Option<DateTime<Utc>>,
+    /// Return only items completed before this RFC 3339 time.
+    pub completed_before: Option<DateTime<Utc>>,
+    /// Return only items due after this RFC 3339 time.
+    pub due_after: Option<DateTime<Utc>>,
+    /// Return only items due before this RFC 3339 time.
+    pub due_before: Option<DateTime<Utc>>,
+    /// Return only items with these tags, repeat the parameter for several tags.
+    #[param(example = json!(["home", "urgent"]))]
+    #[serde(default)]
+    #[validate(custom(function = "validate_tags"))]
+    pub tag: Vec<String>,
+    /// Whether items need all the tags or any of them, defaults to all.
+    pub tag_match: Option<TagsMatch>,
+}
+
+impl TodosQuery {
+    pub fn created_at(&self) -> DbTimeRange {
+        DbTimeRange {
+            after: self.created_after,
+            before: self.created_before,
+        }
+    }
+
+    pub fn updated_at(&self) -> DbTimeRange {
+        DbTimeRange {
+            after: self.updated_after,
+            before: self.updated_before,
+        }
+    }
+
+    pub fn completed_at(&self) -> DbTimeRange {
+        DbTimeRange {
+            after: self.completed_after,
+            before: self.completed_before,
+        }
+    }
+
+    pub fn due_at(&self) -> DbTimeRange {
+        DbTimeRange {
+            after: self.due_after,
+            before: self.due_before,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, ToSchema)]
+pub enum TodosSort {
+    #[serde(rename = "text")]
+    Text,
+    #[serde(rename = "-text")]
+    TextDesc,
+    #[serde(rename = "created_at")]
+    CreatedAt,
+    #[serde(rename = "-created_at")]
+    CreatedAtDesc,
+    #[serde(rename = "updated_at")]
+    UpdatedAt,
+    #[serde(rename = "-updated_at")]
+    UpdatedAtDesc,
+    /// Items without a due date last.
+    #[serde(rename = "due_at")]
+    DueAt,
+    /// Items without a due date first.
+    #[serde(rename = "-due_at")]
+    DueAtDesc,
+    #[serde(rename = "priority")]
+    Priority,
+    #[serde(rename = "-priority")]
+    PriorityDesc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl From<Priority> for DbPriority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => DbPriority::Low,
+            Priority::Medium => DbPriority::Medium,
+            Priority::High => DbPriority::High,
+        }
+    }
+}
+
+impl From<DbPriority> for Priority {
+    fn from(priority: DbPriority) -> Self {
+        match priority {
+            DbPriority::Low => Priority::Low,
+            DbPriority::Medium => Priority::Medium,
+            DbPriority::High => Priority::High,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TagsMatch {
+    All,
+    Any,
+}
+
+impl From<TagsMatch> for DbTagsMatch {
+    fn from(tags_match: TagsMatch) -> Self {
+        match tags_match {
+            TagsMatch::All => DbTagsMatch::All,
+            TagsMatch::Any => DbTagsMatch::Any,
+        }
+    }
+}
+
+impl From<TodosSort> for DbTodosSort {
+    fn from(sort: TodosSort) -> Self {
+        match sort {
+            TodosSort::Text => DbTodosSort::Text,
+            TodosSort::TextDesc => DbTodosSort::TextDesc,
+            TodosSort::CreatedAt => DbTodosSort::CreatedAt,
+            TodosSort::CreatedAtDesc => DbTodosSort::CreatedAtDesc,
+            TodosSort::UpdatedAt => DbTodosSort::UpdatedAt,
+            TodosSort::UpdatedAtDesc => DbTodosSort::UpdatedAtDesc,
+            TodosSort::DueAt => DbTodosSort::DueAt,
+            TodosSort::DueAtDesc => DbTodosSort::DueAtDesc,
+            TodosSort::Priority => DbTodosSort::Priority,
+            TodosSort::PriorityDesc => DbTodosSort::PriorityDesc,
+        }
+    }
 }
 
 /// Item to do.
@@ -16,6 +189,23 @@ pub struct Todo {
     #[schema(example = "Buy groceries")]
     pub text: String,
     pub completed: bool,
+    pub created_at: DateTime<Utc>,
+    /// Time of the last change, same as `created_at` for items never updated.
+    pub updated_at: DateTime<Utc>,
+    /// Time the item was marked as completed, null while not completed.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Incremented by every update, the `ETag` of the item is the quoted version.
+    #[schema(example = 1)]
+    pub version: i64,
+    /// List of the item, null if it is in no list.
+    #[schema(example = "5f0c7a3e-8b1d-4c2a-9e6f-1d2b3c4d5e6f")]
+    pub list_id: Option<String>,
+    /// Tags of the item in ascending order.
+    #[schema(example = json!(["home", "urgent"]))]
+    pub tags: Vec<String>,
+    /// Time the item is due, null if it has no due date.
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
 }
 
 impl From<DbTodo> for Todo {
@@ -24,6 +214,14 @@ impl From<DbTodo> for Todo {
             id: db_todo.id.to_string(),
             text: db_todo.text,
             completed: db_todo.completed,
+            created_at: db_todo.created_at,
+            updated_at: db_todo.updated_at,
+            completed_at: db_todo.completed_at,
+            version: db_todo.version,
+            list_id: db_todo.list_id.map(|id| id.to_string()),
+            tags: db_todo.tags,
+            due_at: db_todo.due_at,
+            priority: db_todo.priority.into(),
         }
     }
 }
@@ -33,6 +231,48 @@ pub struct NewTodo {
     #[schema(example = "Buy groceries")]
     #[validate(length(min = 1, max = 200, message = "length must be between 1 and 200"))]
     pub text: String,
+    /// Adds the item to the list.
+    // skipped so requests without a list hash like before, see `idempotency::request_hash`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "5f0c7a3e-8b1d-4c2a-9e6f-1d2b3c4d5e6f")]
+    pub list_id: Option<Uuid>,
+    /// Tags of the item, duplicates are ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(example = json!(["home", "urgent"]))]
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Vec<String>,
+    /// Time the item is due, an RFC 3339 time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "2025-03-01T17:00:00Z")]
+    #[validate(custom(function = "validate_due_at"))]
+    pub due_at: Option<DateTime<Utc>>,
+    /// Defaults to medium.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+}
+
+/// All the fields of a Todo that can be changed, used to replace it.
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct ReplaceTodo {
+    #[schema(example = "Buy groceries")]
+    #[validate(length(min = 1, max = 200, message = "length must be between 1 and 200"))]
+    pub text: String,
+    pub completed: bool,
+    /// List of the item, the item is removed from its list if missing.
+    #[schema(value_type = Option<String>, example = "5f0c7a3e-8b1d-4c2a-9e6f-1d2b3c4d5e6f")]
+    pub list_id: Option<Uuid>,
+    /// Tags of the item, all tags are removed if missing.
+    #[serde(default)]
+    #[schema(example = json!(["home", "urgent"]))]
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Vec<String>,
+    /// Time the item is due, the due date is removed if missing.
+    #[schema(example = "2025-03-01T17:00:00Z")]
+    #[validate(custom(function = "validate_due_at"))]
+    pub due_at: Option<DateTime<Utc>>,
+    /// Defaults to medium if missing.
+    #[serde(default)]
+    pub priority: Priority,
 }
 
 #[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
@@ -41,6 +281,43 @@ pub struct UpdateTodo {
     #[validate(length(min = 1, max = 200, message = "length must be between 1 and 200"))]
     pub text: Option<String>,
     pub completed: Option<bool>,
+    /// Moves the item to the list.
+    #[schema(value_type = Option<String>, example = "5f0c7a3e-8b1d-4c2a-9e6f-1d2b3c4d5e6f")]
+    pub list_id: Option<Uuid>,
+    /// Replaces all the tags of the item.
+    #[schema(example = json!(["home", "urgent"]))]
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+    #[schema(example = "2025-03-01T17:00:00Z")]
+    #[validate(custom(function = "validate_due_at"))]
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Option<Priority>,
+}
+
+/// Due dates must be earlier than `NO_DUE_AT`, which sorts the items without one.
+pub fn validate_due_at(due_at: &DateTime<Utc>) -> Result<(), ValidationError> {
+    if *due_at >= NO_DUE_AT {
+        return Err(ValidationError::new("due_at")
+            .with_message("must be before 9999-12-31T23:59:59Z".into()));
+    }
+    Ok(())
+}
+
+/// At most `MAX_TAGS` tags, each between 1 and 50 characters.
+pub fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > MAX_TAGS {
+        return Err(ValidationError::new("tags")
+            .with_message(format!("must have at most {MAX_TAGS} tags").into()));
+    }
+    if tags
+        .iter()
+        .any(|tag| tag.is_empty() || tag.chars().count() > 50)
+    {
+        return Err(
+            ValidationError::new("tags").with_message("tag length must be between 1 and 50".into())
+        );
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -52,33 +329,84 @@ mod tests {
     fn test_new_todo_validation() {
         let valid_todo = NewTodo {
             text: "Valid todo".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
         };
         assert!(valid_todo.validate().is_ok());
 
         let empty_todo = NewTodo {
             text: "".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
         };
         assert!(empty_todo.validate().is_err());
         assert_validation_error_message(empty_todo, "length must be between 1 and 200");
 
         let long_todo = NewTodo {
             text: "a".repeat(201),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
         };
         assert!(long_todo.validate().is_err());
         assert_validation_error_message(long_todo, "length must be between 1 and 200");
     }
 
+    #[test]
+    fn test_todos_query_validation() {
+        let default_query = TodosQuery::default();
+        assert!(default_query.validate().is_ok());
+
+        let valid_query = TodosQuery {
+            limit: Some(100),
+            q: Some("groceries".to_string()),
+            ..Default::default()
+        };
+        assert!(valid_query.validate().is_ok());
+
+        let zero_limit = TodosQuery {
+            limit: Some(0),
+            ..Default::default()
+        };
+        assert_validation_error_message(zero_limit, "must be between 1 and 100");
+
+        let large_limit = TodosQuery {
+            limit: Some(101),
+            ..Default::default()
+        };
+        assert_validation_error_message(large_limit, "must be between 1 and 100");
+
+        let empty_search = TodosQuery {
+            q: Some("".to_string()),
+            ..Default::default()
+        };
+        assert_validation_error_message(empty_search, "length must be between 1 and 200");
+    }
+
     #[test]
     fn test_update_todo_validation() {
         let valid_todo = UpdateTodo {
             text: Some("Valid todo".to_string()),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         assert!(valid_todo.validate().is_ok());
 
         let empty_todo = UpdateTodo {
             text: Some("".to_string()),
             completed: Some(false),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         assert!(empty_todo.validate().is_err());
         assert_validation_error_message(empty_todo, "length must be between 1 and 200");
@@ -86,6 +414,10 @@ mod tests {
         let long_todo = UpdateTodo {
             text: Some("a".repeat(201)),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         assert!(long_todo.validate().is_err());
         assert_validation_error_message(long_todo, "length must be between 1 and 200");
@@ -93,16 +425,61 @@ mod tests {
         let no_text_todo = UpdateTodo {
             text: None,
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         assert!(no_text_todo.validate().is_ok());
 
         let nothing_todo = UpdateTodo {
             text: None,
             completed: None,
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         assert!(nothing_todo.validate().is_ok()); // this needs to be validate separately
     }
 
+    #[test]
+    fn test_tags_validation() {
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        assert!(validate_tags(&[]).is_ok());
+        assert!(validate_tags(&tags(&["home", "a", &"é".repeat(50)])).is_ok());
+        assert!(validate_tags(&tags(&[""])).is_err());
+        assert!(validate_tags(&tags(&[&"a".repeat(51)])).is_err());
+        assert!(validate_tags(&vec!["tag".to_string(); MAX_TAGS]).is_ok());
+        assert!(validate_tags(&vec!["tag".to_string(); MAX_TAGS + 1]).is_err());
+
+        let long_tag = UpdateTodo {
+            text: None,
+            completed: None,
+            list_id: None,
+            tags: Some(tags(&[&"a".repeat(51)])),
+            due_at: None,
+            priority: None,
+        };
+        assert_validation_error_message(long_tag, "tag length must be between 1 and 50");
+    }
+
+    #[test]
+    fn test_due_at_validation() {
+        assert!(validate_due_at(&Utc::now()).is_ok());
+        assert!(validate_due_at(&(NO_DUE_AT - chrono::Duration::seconds(1))).is_ok());
+        assert!(validate_due_at(&NO_DUE_AT).is_err());
+
+        let new_todo = NewTodo {
+            text: "test".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: Some(NO_DUE_AT),
+            priority: Some(Priority::High),
+        };
+        assert_validation_error_message(new_todo, "must be before 9999-12-31T23:59:59Z");
+    }
+
     fn assert_validation_error_message<T: Validate>(item: T, expected_message: &str) {
         let error = item.validate().err().unwrap().to_string();
         assert!(error.contains(expected_message));
@@ -1,23 +1,41 @@
-use crate::{datasources::database::DatabaseError, server::domain::errors::ErrorResponse};
+use crate::{
+    datasources::database::DatabaseError,
+    server::domain::errors::{ErrorResponse, FieldError},
+};
 use axum::http::StatusCode;
 use axum::{
     extract::rejection::JsonRejection,
     response::{IntoResponse, Response},
     Json,
 };
-use axum_extra::typed_header::TypedHeaderRejection;
+use axum_extra::{extract::QueryRejection, typed_header::TypedHeaderRejection};
 use tracing::{error, warn};
+use validator::{ValidationErrors, ValidationErrorsKind};
 
 #[derive(thiserror::Error, Debug)]
 pub enum AppError {
     #[error(transparent)]
     JsonRejection(#[from] JsonRejection),
     #[error(transparent)]
+    QueryRejection(#[from] QueryRejection),
+    #[error(transparent)]
     ValidationError(#[from] validator::ValidationErrors),
     #[error("{0}")]
     BadRequest(String),
     #[error("{0}")]
     NotFound(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    PreconditionFailed(String),
+    #[error("{0}")]
+    UnsupportedMediaType(String),
+    #[error("{0}")]
+    UnprocessableEntity(String),
+    #[error("{0}")]
+    TooManyRequests(String),
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
@@ -26,20 +44,89 @@ impl From<DatabaseError> for AppError {
     fn from(error: DatabaseError) -> Self {
         match error {
             DatabaseError::NotFound { .. } => AppError::NotFound(error.to_string()),
+            // the todo refers to a list, the request itself was found
+            DatabaseError::ListNotFound { .. } => AppError::UnprocessableEntity(error.to_string()),
+            DatabaseError::VersionMismatch { .. } => {
+                AppError::PreconditionFailed(error.to_string())
+            }
+            DatabaseError::Batch { source, .. } => AppError::from(*source),
             DatabaseError::Internal(_) => AppError::Unknown(error.into()),
         }
     }
 }
 
+/// Details of an error response, kept in its extensions to render the error as problem details.
+#[derive(Debug, Clone)]
+pub struct ErrorDetails {
+    pub detail: String,
+    pub errors: Vec<FieldError>,
+}
+
+fn error_response(status: StatusCode, message: String, errors: Vec<FieldError>) -> Response {
+    let details = ErrorDetails {
+        detail: message.clone(),
+        errors,
+    };
+    let mut response = (status, Json(ErrorResponse { error: message })).into_response();
+    response.extensions_mut().insert(details);
+    response
+}
+
+/// Errors of every field, also of nested structs and lists, ordered by field.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut field_errors = Vec::new();
+    collect_field_errors(errors, "", &mut field_errors);
+    field_errors.sort_by(|a, b| a.field.cmp(&b.field));
+    field_errors
+}
+
+fn collect_field_errors(errors: &ValidationErrors, path: &str, field_errors: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let field = if path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", path, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                field_errors.extend(errors.iter().map(|error| {
+                    FieldError {
+                        field: field.clone(),
+                        code: error.code.to_string(),
+                        message: error
+                            .message
+                            .as_ref()
+                            .map_or_else(|| error.code.to_string(), |message| message.to_string()),
+                    }
+                }))
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                collect_field_errors(errors, &field, field_errors)
+            }
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(errors, &format!("{}[{}]", field, index), field_errors);
+                }
+            }
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let mut errors = Vec::new();
         let (status, message) = match self {
             AppError::JsonRejection(_) => {
                 warn!("Invalid JSON in request: {:?}", self);
                 (StatusCode::BAD_REQUEST, "failed to read json".to_string())
             }
+            AppError::QueryRejection(_) => {
+                warn!("Invalid query in request: {:?}", self);
+                (StatusCode::BAD_REQUEST, "failed to read query".to_string())
+            }
             AppError::ValidationError(ref error) => {
                 warn!("Validation error: {:?}", self);
+                errors = field_errors(error);
                 (StatusCode::BAD_REQUEST, error.to_string())
             }
             AppError::BadRequest(ref message) => {
@@ -50,6 +137,33 @@ impl IntoResponse for AppError {
                 warn!("Not found: {:?}", self);
                 (StatusCode::NOT_FOUND, "not found".to_string())
             }
+            AppError::Forbidden(ref message) => {
+                warn!("Forbidden: {:?}", self);
+                (StatusCode::FORBIDDEN, message.clone())
+            }
+            AppError::Conflict(ref message) => {
+                warn!("Conflict: {:?}", self);
+                (StatusCode::CONFLICT, message.clone())
+            }
+            AppError::PreconditionFailed(_) => {
+                warn!("Precondition failed: {:?}", self);
+                (
+                    StatusCode::PRECONDITION_FAILED,
+                    "precondition failed".to_string(),
+                )
+            }
+            AppError::UnsupportedMediaType(ref message) => {
+                warn!("Unsupported media type: {:?}", self);
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, message.clone())
+            }
+            AppError::UnprocessableEntity(ref message) => {
+                warn!("Unprocessable entity: {:?}", self);
+                (StatusCode::UNPROCESSABLE_ENTITY, message.clone())
+            }
+            AppError::TooManyRequests(ref message) => {
+                warn!("Too many requests: {:?}", self);
+                (StatusCode::TOO_MANY_REQUESTS, message.clone())
+            }
             AppError::Unknown(_) => {
                 error!("Unknown error: {:?}", self);
                 (
@@ -59,7 +173,7 @@ impl IntoResponse for AppError {
             }
         };
 
-        (status, Json(ErrorResponse { error: message })).into_response()
+        error_response(status, message, errors)
     }
 }
 
@@ -67,8 +181,12 @@ impl IntoResponse for AppError {
 pub enum AuthError {
     #[error("{0}")]
     Failed(String),
+    #[error("{0}")]
+    Forbidden(String),
     #[error(transparent)]
     HeaderRejection(#[from] TypedHeaderRejection),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
 }
 
 impl IntoResponse for AuthError {
@@ -78,6 +196,10 @@ impl IntoResponse for AuthError {
                 warn!("Authentication failed: {}", self);
                 (StatusCode::UNAUTHORIZED, "invalid credentials".to_string())
             }
+            AuthError::Forbidden(_) => {
+                warn!("Authorization failed: {}", self);
+                (StatusCode::FORBIDDEN, "insufficient role".to_string())
+            }
             AuthError::HeaderRejection(_) => {
                 warn!("Authentication header error: {}", self);
                 (
@@ -85,9 +207,16 @@ impl IntoResponse for AuthError {
                     "invalid authentication header".to_string(),
                 )
             }
+            AuthError::Unknown(_) => {
+                error!("Authentication error: {:?}", self);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "unknown error".to_string(),
+                )
+            }
         };
 
-        (status, Json(ErrorResponse { error: message })).into_response()
+        error_response(status, message, Vec::new())
     }
 }
 
@@ -97,7 +226,11 @@ mod tests {
     use crate::test_utils::read_response_body;
     use anyhow::anyhow;
     use axum::extract::rejection::MissingJsonContentType;
+    use axum::extract::FromRequestParts;
+    use axum::http::Request;
     use axum::response::Response;
+    use axum_extra::extract::Query;
+    use std::collections::HashMap;
 
     #[tokio::test]
     async fn test_json_extractor_rejection() {
@@ -110,6 +243,20 @@ mod tests {
         assert_eq!(response_body.error, "failed to read json");
     }
 
+    #[tokio::test]
+    async fn test_query_extractor_rejection() {
+        let (mut parts, _) = Request::get("/?limit=abc").body(()).unwrap().into_parts();
+        let query_rejection = Query::<HashMap<String, u32>>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+        let app_error = AppError::QueryRejection(query_rejection);
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "failed to read query");
+    }
+
     #[tokio::test]
     async fn test_validation_error() {
         let mut validation_errors = validator::ValidationErrors::new();
@@ -126,6 +273,46 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_validation_error_details() {
+        let mut validation_errors = validator::ValidationErrors::new();
+        validation_errors.add(
+            "text",
+            validator::ValidationError::new("length").with_message("too long".into()),
+        );
+        let response = AppError::ValidationError(validation_errors).into_response();
+
+        let details = response.extensions().get::<ErrorDetails>().unwrap();
+        assert_eq!(details.detail, "text: too long");
+        assert_eq!(
+            details.errors,
+            vec![FieldError {
+                field: "text".to_string(),
+                code: "length".to_string(),
+                message: "too long".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_field_errors_nested() {
+        use crate::server::domain::batch::BatchRequest;
+        use validator::Validate;
+
+        let request: BatchRequest = serde_json::from_value(serde_json::json!({
+            "operations": [
+                { "op": "create", "text": "valid" },
+                { "op": "create", "text": "" },
+            ]
+        }))
+        .unwrap();
+        let errors = field_errors(&request.validate().unwrap_err());
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "operations[1].text");
+        assert_eq!(errors[0].message, "length must be between 1 and 200");
+    }
+
     #[tokio::test]
     async fn test_bad_request() {
         let app_error = AppError::BadRequest("too long input".into());
@@ -137,6 +324,50 @@ mod tests {
         assert_eq!(response_body.error, "too long input");
     }
 
+    #[tokio::test]
+    async fn test_unsupported_media_type() {
+        let app_error = AppError::UnsupportedMediaType("content type not supported".into());
+
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "content type not supported");
+    }
+
+    #[tokio::test]
+    async fn test_unprocessable_entity() {
+        let app_error = AppError::UnprocessableEntity("patch cannot be applied".into());
+
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "patch cannot be applied");
+    }
+
+    #[tokio::test]
+    async fn test_conflict() {
+        let app_error = AppError::Conflict("request in progress".into());
+
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "request in progress");
+    }
+
+    #[tokio::test]
+    async fn test_too_many_requests() {
+        let app_error = AppError::TooManyRequests("too many requests".into());
+
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "too many requests");
+    }
+
     #[tokio::test]
     async fn test_not_found() {
         let app_error: AppError = AppError::NotFound("not found".to_string());
@@ -161,6 +392,38 @@ mod tests {
         assert_eq!(response_body.error, format!("not found"));
     }
 
+    #[tokio::test]
+    async fn test_database_list_not_found() {
+        let id = uuid::Uuid::new_v4();
+        let db_error = DatabaseError::ListNotFound { id };
+        let app_error: AppError = db_error.into();
+
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            format!("list not found with id: {}", id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_database_version_mismatch() {
+        let id = uuid::Uuid::new_v4();
+        let db_error = DatabaseError::VersionMismatch {
+            id,
+            expected_version: 1,
+        };
+        let app_error: AppError = db_error.into();
+
+        let response: Response = app_error.into_response();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "precondition failed");
+    }
+
     #[tokio::test]
     async fn test_database_internal_error() {
         let db_error = DatabaseError::Internal(anyhow!("internal error"));
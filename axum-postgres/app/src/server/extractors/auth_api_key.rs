@@ -0,0 +1,195 @@
+use crate::server::{api_keys::hash_api_key, errors::AuthError, roles::Role};
+use crate::SharedState;
+use axum::RequestPartsExt;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{request::Parts, HeaderName, HeaderValue},
+};
+use axum_extra::headers::{self, Header};
+use axum_extra::TypedHeader;
+use tracing::{field, Span};
+
+pub static X_API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+
+/// `X-API-Key` header with a key from `POST /api/v1/auth/keys`.
+pub struct XApiKey(pub String);
+
+impl Header for XApiKey {
+    fn name() -> &'static HeaderName {
+        &X_API_KEY
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        values
+            .next()
+            .and_then(|value| value.to_str().ok())
+            .filter(|key| !key.is_empty())
+            .map(|key| XApiKey(key.to_string()))
+            .ok_or_else(headers::Error::invalid)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        if let Ok(value) = HeaderValue::from_str(&self.0) {
+            values.extend(std::iter::once(value));
+        }
+    }
+}
+
+/// Owner and role of a valid API key. The role is capped by the current role of the owner, so
+/// keys stop working when the owner is removed from the credentials.
+#[derive(Clone)]
+pub struct AuthApiKey(pub String, pub Role);
+
+impl<S> FromRequestParts<S> for AuthApiKey
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(auth) = parts.extensions.get::<Self>() {
+            return Ok(auth.clone());
+        }
+
+        let TypedHeader(XApiKey(key)) = parts.extract::<TypedHeader<XApiKey>>().await?;
+
+        let state = SharedState::from_ref(state);
+        let api_key = state
+            .db
+            .find_api_key(&hash_api_key(&key))
+            .await
+            .map_err(anyhow::Error::from)?
+            .ok_or_else(|| AuthError::Failed("api key not valid".to_string()))?;
+        let owner_role = state.credentials.role(&api_key.owner).ok_or_else(|| {
+            AuthError::Failed(format!("owner of api key {} not found", api_key.id))
+        })?;
+        let key_role: Role = api_key.role.parse().map_err(anyhow::Error::msg)?;
+
+        // Record the user in the current span
+        let span = Span::current();
+        span.record("user", field::display(&api_key.owner));
+
+        let auth = Self(api_key.owner, key_role.min(owner_role));
+        parts.extensions.insert(auth.clone());
+        Ok(auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasources::database::models::DbApiKey;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::errors::AppError;
+    use crate::test_utils::{init_router, read_response_body, test_with_headers};
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    async fn test_auth(AuthApiKey(user, role): AuthApiKey) -> Result<String, AppError> {
+        Ok(format!("{}:{}", user, role))
+    }
+
+    fn db_api_key(owner: &str, role: &str) -> DbApiKey {
+        DbApiKey {
+            id: Uuid::new_v4(),
+            owner: owner.to_string(),
+            name: "batch job".to_string(),
+            role: role.to_string(),
+            prefix: "tdk_abcdefgh".to_string(),
+            created_at: Utc::now(),
+            revoked_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_valid_api_key() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_find_api_key()
+            .withf(|key_hash| key_hash == hash_api_key("tdk_key"))
+            .returning(|_| Ok(Some(db_api_key("user", "viewer"))));
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let headers = [("X-API-Key", "tdk_key")];
+        let response = test_with_headers(app, "GET", "/protected", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "user:viewer");
+    }
+
+    #[tokio::test]
+    async fn test_api_key_role_capped_by_owner() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_find_api_key()
+            .returning(|_| Ok(Some(db_api_key("user", "admin"))));
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let headers = [("X-API-Key", "tdk_key")];
+        let response = test_with_headers(app, "GET", "/protected", &headers, None).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "user:editor");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_api_key() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_find_api_key().returning(|_| Ok(None));
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let headers = [("X-API-Key", "tdk_unknown")];
+        let response = test_with_headers(app, "GET", "/protected", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "invalid credentials");
+    }
+
+    #[tokio::test]
+    async fn test_api_key_of_removed_user() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_find_api_key()
+            .returning(|_| Ok(Some(db_api_key("removed", "viewer"))));
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let headers = [("X-API-Key", "tdk_key")];
+        let response = test_with_headers(app, "GET", "/protected", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_database_error() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_find_api_key()
+            .returning(|_| Err(DatabaseError::Internal(anyhow::anyhow!("connection lost"))));
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let headers = [("X-API-Key", "tdk_key")];
+        let response = test_with_headers(app, "GET", "/protected", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_missing_api_key_header() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let headers = [("X-API-Key", "")];
+        let response = test_with_headers(app, "GET", "/protected", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "invalid authentication header");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::server::errors::AuthError;
+use crate::server::{errors::AuthError, roles::Role};
 use crate::SharedState;
 use axum::RequestPartsExt;
 use axum::{
@@ -9,7 +9,10 @@ use axum_extra::headers::{authorization::Basic, Authorization};
 use axum_extra::TypedHeader;
 use tracing::{field, Span};
 
-pub struct AuthBasic(pub String);
+/// User and role of valid Basic credentials. The result is kept in the request extensions, so the
+/// slow password verification runs once per request.
+#[derive(Clone)]
+pub struct AuthBasic(pub String, pub Role);
 
 impl<S> FromRequestParts<S> for AuthBasic
 where
@@ -19,19 +22,29 @@ where
     type Rejection = AuthError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(auth) = parts.extensions.get::<Self>() {
+            return Ok(auth.clone());
+        }
+
         let TypedHeader(Authorization(basic)) =
             parts.extract::<TypedHeader<Authorization<Basic>>>().await?;
-        let header_credentials = (basic.username().to_string(), basic.password().to_string());
-
         let state = SharedState::from_ref(state);
-        if state.credentials.contains(&header_credentials) {
-            // Record the user in the current span
-            let span = Span::current();
-            span.record("user", field::display(basic.username()));
-
-            Ok(Self(basic.username().to_string()))
-        } else {
-            Err(AuthError::Failed("credentials not valid".to_string()))
+        let (user, password) = (basic.username().to_string(), basic.password().to_string());
+        // password hashing is slow, keep it off the async workers
+        let role = tokio::task::spawn_blocking(move || state.credentials.verify(&user, &password))
+            .await
+            .unwrap_or(None);
+        match role {
+            Some(role) => {
+                // Record the user in the current span
+                let span = Span::current();
+                span.record("user", field::display(basic.username()));
+
+                let auth = Self(basic.username().to_string(), role);
+                parts.extensions.insert(auth.clone());
+                Ok(auth)
+            }
+            None => Err(AuthError::Failed("credentials not valid".to_string())),
         }
     }
 }
@@ -39,7 +52,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::datasources::database::MockDatabase;
+    use crate::datasources::database::MockTodoRepository;
     use crate::server::domain::errors::ErrorResponse;
     use crate::server::errors::AppError;
     use crate::test_utils::{init_router, read_response_body, test_authenticated};
@@ -47,13 +60,13 @@ mod tests {
     use axum::routing::get;
     use base64::Engine;
 
-    async fn test_auth(AuthBasic(_): AuthBasic) -> Result<(), AppError> {
+    async fn test_auth(AuthBasic(_, _): AuthBasic) -> Result<(), AppError> {
         Ok(())
     }
 
     #[tokio::test]
     async fn test_valid_credentials() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/protected", get(test_auth)).await;
 
         let header = &format!(
@@ -66,7 +79,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_invalid_credentials() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/protected", get(test_auth)).await;
 
         let header = &format!(
@@ -82,7 +95,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_empty_credentials() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/protected", get(test_auth)).await;
 
         let header = &format!(
@@ -98,7 +111,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_missing_authorization_header() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/protected", get(test_auth)).await;
 
         let response = test_authenticated(app, "/protected", "GET", "").await;
@@ -110,7 +123,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_malformed_authorization_header() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/protected", get(test_auth)).await;
 
         let header = "Basic malformed_header";
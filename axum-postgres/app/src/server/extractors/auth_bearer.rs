@@ -0,0 +1,137 @@
+use crate::server::{errors::AuthError, roles::Role};
+use crate::SharedState;
+use axum::RequestPartsExt;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use axum_extra::headers::{authorization::Bearer, Authorization};
+use axum_extra::TypedHeader;
+use tracing::{field, Span};
+
+/// User and role of a valid access token from `POST /api/v1/auth/token`. The role is at most the
+/// current role of the user, so that tokens do not outlive a demotion or removal of the user.
+#[derive(Clone)]
+pub struct AuthBearer(pub String, pub Role);
+
+impl<S> FromRequestParts<S> for AuthBearer
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(auth) = parts.extensions.get::<Self>() {
+            return Ok(auth.clone());
+        }
+
+        let TypedHeader(Authorization(bearer)) = parts
+            .extract::<TypedHeader<Authorization<Bearer>>>()
+            .await?;
+
+        let state = SharedState::from_ref(state);
+        let claims = state
+            .tokens
+            .validate(bearer.token())
+            .map_err(|e| AuthError::Failed(format!("token not valid: {}", e)))?;
+        let user_role = state
+            .credentials
+            .role(&claims.sub)
+            .ok_or_else(|| AuthError::Failed(format!("user {} of token not found", claims.sub)))?;
+
+        // Record the user in the current span
+        let span = Span::current();
+        span.record("user", field::display(&claims.sub));
+
+        let auth = Self(claims.sub, claims.role.min(user_role));
+        parts.extensions.insert(auth.clone());
+        Ok(auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::errors::AppError;
+    use crate::server::tokens::TokenKeys;
+    use crate::test_utils::{
+        init_router, read_response_body, test_authenticated, test_bearer, test_bearer_role,
+    };
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use std::time::Duration;
+
+    async fn test_auth(AuthBearer(user, role): AuthBearer) -> Result<String, AppError> {
+        Ok(format!("{}:{}", user, role))
+    }
+
+    #[tokio::test]
+    async fn test_valid_token() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let response = test_authenticated(app, "/protected", "GET", &test_bearer("user")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "user:editor");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_token() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let token = TokenKeys::random(Duration::from_secs(60))
+            .issue("user", Role::Editor)
+            .unwrap();
+        let header = &format!("Bearer {}", token);
+        let response = test_authenticated(app, "/protected", "GET", header).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "invalid credentials");
+    }
+
+    #[tokio::test]
+    async fn test_token_role_capped_by_user() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        // the editor `user` may have been an admin when the token was issued
+        let header = &test_bearer_role("user", Role::Admin);
+        let response = test_authenticated(app, "/protected", "GET", header).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "user:editor");
+    }
+
+    #[tokio::test]
+    async fn test_token_of_removed_user() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let response = test_authenticated(app, "/protected", "GET", &test_bearer("alice")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "invalid credentials");
+    }
+
+    #[tokio::test]
+    async fn test_missing_authorization_header() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let response = test_authenticated(app, "/protected", "GET", "").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "invalid authentication header");
+    }
+}
@@ -0,0 +1,72 @@
+use crate::server::errors::AuthError;
+use crate::server::extractors::{auth_basic::AuthBasic, auth_bearer::AuthBearer};
+use crate::server::roles::Role;
+use crate::SharedState;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts},
+};
+
+/// User and role authenticated with an access token or with Basic credentials, selected by the
+/// scheme of the `Authorization` header.
+pub struct AuthUser(pub String, pub Role);
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let is_bearer = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split_once(' '))
+            .is_some_and(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"));
+
+        if is_bearer {
+            let AuthBearer(user, role) = AuthBearer::from_request_parts(parts, state).await?;
+            Ok(Self(user, role))
+        } else {
+            let AuthBasic(user, role) = AuthBasic::from_request_parts(parts, state).await?;
+            Ok(Self(user, role))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::errors::AppError;
+    use crate::test_utils::{init_router, test_authenticated, test_bearer};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use base64::Engine;
+
+    async fn test_auth(AuthUser(_, _): AuthUser) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_basic_or_bearer() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(test_auth)).await;
+
+        let basic = &format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode("user:pass".as_bytes())
+        );
+        let response = test_authenticated(app.clone(), "/protected", "GET", basic).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response =
+            test_authenticated(app.clone(), "/protected", "GET", &test_bearer("user")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = test_authenticated(app, "/protected", "GET", "Bearer invalid").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
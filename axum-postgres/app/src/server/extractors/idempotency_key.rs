@@ -0,0 +1,88 @@
+use crate::server::errors::AppError;
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderName},
+};
+
+pub static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Key of the `Idempotency-Key` header, `None` if the header is missing.
+pub struct IdempotencyKey(pub Option<String>);
+
+impl<S> FromRequestParts<S> for IdempotencyKey
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(&IDEMPOTENCY_KEY) else {
+            return Ok(IdempotencyKey(None));
+        };
+        let key = header
+            .to_str()
+            .ok()
+            .map(|value| value.trim())
+            // the key may be sent as a quoted string
+            .map(|value| {
+                value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value)
+            })
+            .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Idempotency-Key header must have 1 to {} characters",
+                    MAX_KEY_LENGTH
+                ))
+            })?;
+        Ok(IdempotencyKey(Some(key.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn extract(key: Option<&str>) -> Result<Option<String>, AppError> {
+        let mut request = Request::builder();
+        if let Some(key) = key {
+            request = request.header(&IDEMPOTENCY_KEY, key);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        IdempotencyKey::from_request_parts(&mut parts, &())
+            .await
+            .map(|IdempotencyKey(key)| key)
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key() {
+        assert_eq!(extract(None).await.unwrap(), None);
+        assert_eq!(extract(Some("abc")).await.unwrap().as_deref(), Some("abc"));
+        assert_eq!(
+            extract(Some("\"abc\"")).await.unwrap().as_deref(),
+            Some("abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_invalid() {
+        assert!(matches!(
+            extract(Some("")).await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            extract(Some("\"\"")).await,
+            Err(AppError::BadRequest(_))
+        ));
+        let too_long = "a".repeat(MAX_KEY_LENGTH + 1);
+        assert!(matches!(
+            extract(Some(&too_long)).await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
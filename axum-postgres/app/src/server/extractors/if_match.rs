@@ -0,0 +1,78 @@
+use crate::server::{domain::etag::parse_todo_etag, errors::AppError};
+use axum::{
+    extract::FromRequestParts,
+    http::{header::IF_MATCH, request::Parts},
+};
+
+/// Version of the todo required by the `If-Match` header, `None` if any version is
+/// accepted because the header is missing or `*`.
+pub struct IfMatchVersion(pub Option<i64>);
+
+impl<S> FromRequestParts<S> for IfMatchVersion
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(IF_MATCH) else {
+            return Ok(IfMatchVersion(None));
+        };
+        let value = header
+            .to_str()
+            .map_err(|_| AppError::BadRequest("If-Match header is not valid".to_string()))?
+            .trim();
+        if value == "*" {
+            return Ok(IfMatchVersion(None));
+        }
+        if value.contains(',') {
+            return Err(AppError::BadRequest(
+                "If-Match with several entity tags is not supported".to_string(),
+            ));
+        }
+        // tags that are not a version, like weak tags, can never match
+        let version = parse_todo_etag(value).ok_or_else(|| {
+            AppError::PreconditionFailed(format!("If-Match does not match any version: {}", value))
+        })?;
+        Ok(IfMatchVersion(Some(version)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn extract(if_match: Option<&str>) -> Result<Option<i64>, AppError> {
+        let mut request = Request::builder();
+        if let Some(if_match) = if_match {
+            request = request.header(IF_MATCH, if_match);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        IfMatchVersion::from_request_parts(&mut parts, &())
+            .await
+            .map(|IfMatchVersion(version)| version)
+    }
+
+    #[tokio::test]
+    async fn test_if_match_version() {
+        assert_eq!(extract(None).await.unwrap(), None);
+        assert_eq!(extract(Some("*")).await.unwrap(), None);
+        assert_eq!(extract(Some("\"3\"")).await.unwrap(), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_if_match_not_a_version() {
+        let weak = extract(Some("W/\"3\"")).await;
+        assert!(matches!(weak, Err(AppError::PreconditionFailed(_))));
+
+        let unquoted = extract(Some("3")).await;
+        assert!(matches!(unquoted, Err(AppError::PreconditionFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_if_match_several_tags() {
+        let several = extract(Some("\"3\", \"4\"")).await;
+        assert!(matches!(several, Err(AppError::BadRequest(_))));
+    }
+}
@@ -1,2 +1,9 @@
+pub mod auth_api_key;
 pub mod auth_basic;
+pub mod auth_bearer;
+pub mod auth_user;
+pub mod idempotency_key;
+pub mod if_match;
 pub mod request_json;
+pub mod request_query;
+pub mod require_role;
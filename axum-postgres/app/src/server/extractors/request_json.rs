@@ -26,7 +26,7 @@ where
 mod tests {
     use super::*;
     use crate::{
-        datasources::database::MockDatabase,
+        datasources::database::MockTodoRepository,
         server::domain::errors::ErrorResponse,
         test_utils::{init_router, read_response_body, test_post},
     };
@@ -47,7 +47,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_valid_json() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/json", post(test_handler)).await;
 
         let payload = TestPayload {
@@ -59,7 +59,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_invalid_json() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/json", post(test_handler)).await;
 
         let payload = TestPayload {
@@ -74,7 +74,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_malformed_json() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/json", post(test_handler)).await;
 
         #[derive(Debug, Deserialize, Serialize)]
@@ -0,0 +1,80 @@
+use crate::server::errors::AppError;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use axum_extra::extract::Query;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+/// Query string validated with its `Validate` rules. Repeated keys, e.g. `tag=a&tag=b`, are
+/// read into a `Vec`.
+pub struct ValidatedQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state).await?;
+        value.validate()?;
+        Ok(ValidatedQuery(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        datasources::database::MockTodoRepository,
+        server::domain::errors::ErrorResponse,
+        test_utils::{init_router, read_response_body, test_get},
+    };
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use serde::Deserialize;
+    use validator::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct TestParams {
+        #[validate(range(min = 1, message = "must be positive"))]
+        field: u32,
+    }
+
+    async fn test_handler(ValidatedQuery(_): ValidatedQuery<TestParams>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_valid_query() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/query", get(test_handler)).await;
+
+        let response = test_get(app, "/query?field=1").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_query() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/query", get(test_handler)).await;
+
+        let response = test_get(app, "/query?field=0").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "field: must be positive");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_query() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/query", get(test_handler)).await;
+
+        let response = test_get(app, "/query?field=abc").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "failed to read query");
+    }
+}
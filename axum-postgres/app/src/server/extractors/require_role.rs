@@ -0,0 +1,146 @@
+use crate::server::errors::AuthError;
+use crate::server::extractors::{
+    auth_api_key::{AuthApiKey, X_API_KEY},
+    auth_user::AuthUser,
+};
+use crate::server::roles::RequiredRole;
+use crate::SharedState;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use std::marker::PhantomData;
+
+/// User authenticated with an `X-API-Key` header if present, otherwise like `AuthUser`, with at
+/// least the role `R`. Users with a lower role are rejected with 403.
+pub struct RequireRole<R: RequiredRole>(pub String, pub PhantomData<R>);
+
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+    R: RequiredRole,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let (user, role) = if parts.headers.contains_key(&X_API_KEY) {
+            let AuthApiKey(user, role) = AuthApiKey::from_request_parts(parts, state).await?;
+            (user, role)
+        } else {
+            let AuthUser(user, role) = AuthUser::from_request_parts(parts, state).await?;
+            (user, role)
+        };
+        if role < R::ROLE {
+            return Err(AuthError::Forbidden(format!(
+                "user {} has role {}, requires {}",
+                user,
+                role,
+                R::ROLE
+            )));
+        }
+        Ok(Self(user, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasources::database::models::DbApiKey;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::errors::AppError;
+    use crate::server::roles::{Admin, Editor, Role, Viewer};
+    use crate::test_utils::{
+        init_router, read_response_body, test_authenticated, test_bearer, test_bearer_role,
+        test_state, test_with_headers,
+    };
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    async fn test_admin(RequireRole(_, _): RequireRole<Admin>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn test_editor(RequireRole(_, _): RequireRole<Editor>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn test_viewer(RequireRole(_, _): RequireRole<Viewer>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_require_role() {
+        let cases = [
+            (
+                Role::Viewer,
+                StatusCode::OK,
+                StatusCode::FORBIDDEN,
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                Role::Editor,
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::FORBIDDEN,
+            ),
+            (Role::Admin, StatusCode::OK, StatusCode::OK, StatusCode::OK),
+        ];
+        for (role, viewer, editor, admin) in cases {
+            let header = &test_bearer_role("admin", role);
+            for (handler, uri, expected) in [
+                (get(test_viewer), "/viewer", viewer),
+                (get(test_editor), "/editor", editor),
+                (get(test_admin), "/admin", admin),
+            ] {
+                let app = init_router(MockTodoRepository::new(), uri, handler).await;
+                let response = test_authenticated(app, uri, "GET", header).await;
+                assert_eq!(response.status(), expected, "{} on {}", role, uri);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_require_role_api_key() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_find_api_key().returning(|_| {
+            Ok(Some(DbApiKey {
+                id: Uuid::new_v4(),
+                owner: "user".to_string(),
+                name: "batch job".to_string(),
+                role: "viewer".to_string(),
+                prefix: "tdk_abcdefgh".to_string(),
+                created_at: Utc::now(),
+                revoked_at: None,
+            }))
+        });
+        let app = Router::new()
+            .route("/viewer", get(test_viewer))
+            .route("/editor", get(test_editor))
+            .with_state(test_state(mock_db));
+
+        let headers = [("X-API-Key", "tdk_key")];
+        let response = test_with_headers(app.clone(), "GET", "/viewer", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = test_with_headers(app, "GET", "/editor", &headers, None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_require_role_forbidden() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/admin", get(test_admin)).await;
+
+        let response = test_authenticated(app.clone(), "/admin", "GET", &test_bearer("user")).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "insufficient role");
+
+        let response = test_authenticated(app, "/admin", "GET", "Bearer invalid").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            todos::{TodosQuery, TodosResponse},
+        },
+        errors::AppError,
+        extractors::{request_query::ValidatedQuery, require_role::RequireRole},
+        handlers::todos_list::list_todos,
+        openapi::ADMIN_TAG,
+        roles::Admin,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+
+/// List Todo items of a user
+///
+/// List the Todo items of the given user one page at a time, with the same query parameters as listing own todos.
+#[utoipa::path(
+    get,
+    path = "/users/{user}/todos",
+    tag = ADMIN_TAG,
+    params(
+        ("user" = String, Path, description = "Owner of the todos"),
+        TodosQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched page"),
+    ),
+    responses(
+        (status = 200, description = "List todos successfully", body = TodosResponse,
+            headers(("ETag" = String, description = "Version of the page"))),
+        (status = 304, description = "Page not modified since the given ETag"),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "limit: must be between 1 and 100".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["admin"]),
+        ("bearer_auth" = ["admin"]),
+        ("api_key" = ["admin"])
+    )
+)]
+pub async fn admin_todos_list(
+    Path(owner): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    headers: HeaderMap,
+    ValidatedQuery(query): ValidatedQuery<TodosQuery>,
+) -> Result<Response, AppError> {
+    tracing::info!("Admin {} listed todos of user {}", admin, owner);
+    list_todos(&state, &owner, None, &headers, query).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::todos::TodosResponse;
+    use crate::server::handlers::admin_todos_list::admin_todos_list;
+    use crate::server::roles::Role;
+    use crate::test_utils::{
+        db_todo, init_router, read_response_body, test_authenticated, test_bearer_role,
+    };
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_admin_todos_list() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(|owner, query| owner == "alice" && query.limit == 51)
+            .returning(|owner, _| Ok(vec![db_todo(Uuid::new_v4(), owner, "Alice's todo")]));
+        let app = init_router(mock_db, "/users/{user}/todos", get(admin_todos_list)).await;
+
+        let header = &test_bearer_role("admin", Role::Admin);
+        let response = test_authenticated(app, "/users/alice/todos", "GET", header).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TodosResponse = read_response_body(response).await;
+        assert_eq!(response_body.todos.len(), 1);
+        assert_eq!(response_body.todos[0].text, "Alice's todo");
+    }
+
+    #[tokio::test]
+    async fn test_admin_todos_list_forbidden() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/users/{user}/todos", get(admin_todos_list)).await;
+
+        let header = &test_bearer_role("user", Role::Editor);
+        let response = test_authenticated(app, "/users/user/todos", "GET", header).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    server::{
+        domain::{admin::PurgeResponse, errors::ErrorResponse},
+        errors::AppError,
+        extractors::require_role::RequireRole,
+        openapi::ADMIN_TAG,
+        roles::Admin,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Purge Todo items of a user
+///
+/// Delete all Todo items of the given user, for example after the user was removed from the credentials.
+#[utoipa::path(
+    delete,
+    path = "/users/{user}/todos",
+    tag = ADMIN_TAG,
+    params(
+        ("user" = String, Path, description = "Owner of the todos"),
+    ),
+    responses(
+        (status = 200, description = "Todos purged successfully", body = PurgeResponse),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["admin"]),
+        ("bearer_auth" = ["admin"]),
+        ("api_key" = ["admin"])
+    )
+)]
+pub async fn admin_todos_purge(
+    Path(owner): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+) -> Result<Json<PurgeResponse>, AppError> {
+    let removed = state.db.purge(&owner).await?;
+    tracing::info!("Admin {} purged {} todos of user {}", admin, removed, owner);
+
+    Ok(Json(PurgeResponse { removed }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::admin::PurgeResponse;
+    use crate::server::handlers::admin_todos_purge::admin_todos_purge;
+    use crate::server::roles::Role;
+    use crate::test_utils::{
+        init_router, read_response_body, test_authenticated, test_bearer_role,
+    };
+    use axum::http::StatusCode;
+    use axum::routing::delete;
+
+    #[tokio::test]
+    async fn test_admin_todos_purge() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_purge()
+            .withf(|owner| owner == "alice")
+            .returning(|_| Ok(3));
+        let app = init_router(mock_db, "/users/{user}/todos", delete(admin_todos_purge)).await;
+
+        let header = &test_bearer_role("admin", Role::Admin);
+        let response = test_authenticated(app, "/users/alice/todos", "DELETE", header).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: PurgeResponse = read_response_body(response).await;
+        assert_eq!(response_body.removed, 3);
+    }
+
+    #[tokio::test]
+    async fn test_admin_todos_purge_forbidden() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/users/{user}/todos", delete(admin_todos_purge)).await;
+
+        let header = &test_bearer_role("user", Role::Editor);
+        let response = test_authenticated(app, "/users/alice/todos", "DELETE", header).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
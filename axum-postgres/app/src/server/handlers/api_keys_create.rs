@@ -0,0 +1,175 @@
+use crate::{
+    datasources::database::models::DbNewApiKey,
+    server::{
+        api_keys::generate_api_key,
+        domain::{
+            api_keys::{ApiKey, CreatedApiKey, NewApiKey},
+            errors::ErrorResponse,
+        },
+        errors::AppError,
+        extractors::{auth_user::AuthUser, request_json::ValidatedJson},
+        openapi::AUTH_TAG,
+    },
+    SharedState,
+};
+use axum::{extract::State, http::StatusCode, Json};
+
+/// Create API key
+///
+/// Creates a key for service clients which authenticates as the user with the `X-API-Key` header on the todos endpoints.
+/// The key is only returned in this response. Keys can only be managed with the credentials or an access token of the user.
+#[utoipa::path(
+    post,
+    path = "/keys",
+    tag = AUTH_TAG,
+    request_body = NewApiKey,
+    responses(
+        (status = 201, description = "API key created", body = CreatedApiKey),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "name: length must be between 1 and 100".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the key is higher than the role of the user", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "role of the key cannot be higher than editor".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn api_keys_create(
+    State(state): State<SharedState>,
+    AuthUser(user, user_role): AuthUser,
+    ValidatedJson(input): ValidatedJson<NewApiKey>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), AppError> {
+    let role = input.role.unwrap_or(user_role);
+    if role > user_role {
+        return Err(AppError::Forbidden(format!(
+            "role of the key cannot be higher than {}",
+            user_role
+        )));
+    }
+
+    let generated = generate_api_key();
+    let db_api_key = state
+        .db
+        .insert_api_key(
+            &user,
+            DbNewApiKey {
+                name: input.name,
+                role: role.to_string(),
+                prefix: generated.prefix,
+                key_hash: generated.hash,
+            },
+        )
+        .await?;
+    let api_key = ApiKey::try_from(db_api_key)?;
+    tracing::info!("User {} created api key {}", user, api_key.id);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKey {
+            key: generated.key,
+            api_key,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbApiKey;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::api_keys::hash_api_key;
+    use crate::server::domain::api_keys::CreatedApiKey;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::handlers::api_keys_create::api_keys_create;
+    use crate::server::roles::Role;
+    use crate::test_utils::{init_router, read_response_body, test_post, test_with_headers};
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use chrono::Utc;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_api_keys_create() {
+        let stored_hash = Arc::new(Mutex::new(String::new()));
+        let stored = stored_hash.clone();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_api_key()
+            .withf(|owner, key| owner == "user" && key.name == "Nightly import")
+            .returning(move |owner, key| {
+                *stored.lock().unwrap() = key.key_hash;
+                Ok(DbApiKey {
+                    id: Uuid::new_v4(),
+                    owner: owner.to_string(),
+                    name: key.name,
+                    role: key.role,
+                    prefix: key.prefix,
+                    created_at: Utc::now(),
+                    revoked_at: None,
+                })
+            });
+        let app = init_router(mock_db, "/auth/keys", post(api_keys_create)).await;
+
+        let response = test_post(app, "/auth/keys", json!({ "name": "Nightly import" })).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let created: CreatedApiKey = read_response_body(response).await;
+        assert_eq!(created.api_key.role, Role::Editor);
+        assert!(created.key.starts_with(&created.api_key.prefix));
+        // only the hash of the key is stored
+        assert_eq!(*stored_hash.lock().unwrap(), hash_api_key(&created.key));
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_create_higher_role() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/auth/keys", post(api_keys_create)).await;
+
+        let body = json!({ "name": "Nightly import", "role": "admin" });
+        let response = test_post(app, "/auth/keys", body).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "role of the key cannot be higher than editor"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_create_with_api_key() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/auth/keys", post(api_keys_create)).await;
+
+        // keys cannot create other keys
+        let response = test_with_headers(
+            app,
+            "POST",
+            "/auth/keys",
+            &[("Authorization", ""), ("X-API-Key", "tdk_key")],
+            Some(json!({ "name": "Nightly import" })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_create_invalid_name() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/auth/keys", post(api_keys_create)).await;
+
+        let response = test_post(app, "/auth/keys", json!({ "name": "" })).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "name: length must be between 1 and 100"
+        );
+    }
+}
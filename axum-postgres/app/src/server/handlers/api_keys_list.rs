@@ -0,0 +1,88 @@
+use crate::{
+    server::{
+        domain::{
+            api_keys::{ApiKey, ApiKeysResponse},
+            errors::ErrorResponse,
+        },
+        errors::AppError,
+        extractors::auth_user::AuthUser,
+        openapi::AUTH_TAG,
+    },
+    SharedState,
+};
+use axum::{extract::State, Json};
+
+/// List API keys
+///
+/// List the API keys of the user, also the revoked ones, oldest first.
+#[utoipa::path(
+    get,
+    path = "/keys",
+    tag = AUTH_TAG,
+    responses(
+        (status = 200, description = "List API keys successfully", body = ApiKeysResponse),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn api_keys_list(
+    State(state): State<SharedState>,
+    AuthUser(user, _): AuthUser,
+) -> Result<Json<ApiKeysResponse>, AppError> {
+    let api_keys = state
+        .db
+        .get_api_keys(&user)
+        .await?
+        .into_iter()
+        .map(ApiKey::try_from)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(ApiKeysResponse { api_keys }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbApiKey;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::api_keys::ApiKeysResponse;
+    use crate::server::handlers::api_keys_list::api_keys_list;
+    use crate::server::roles::Role;
+    use crate::test_utils::{init_router, read_response_body, test_get};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_api_keys_list() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_api_keys()
+            .withf(|owner| owner == "user")
+            .returning(|owner| {
+                Ok(vec![DbApiKey {
+                    id: Uuid::new_v4(),
+                    owner: owner.to_string(),
+                    name: "Nightly import".to_string(),
+                    role: "viewer".to_string(),
+                    prefix: "tdk_abcdefgh".to_string(),
+                    created_at: Utc::now(),
+                    revoked_at: Some(Utc::now()),
+                }])
+            });
+        let app = init_router(mock_db, "/auth/keys", get(api_keys_list)).await;
+
+        let response = test_get(app, "/auth/keys").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: ApiKeysResponse = read_response_body(response).await;
+        assert_eq!(response_body.api_keys.len(), 1);
+        assert_eq!(response_body.api_keys[0].role, Role::Viewer);
+        assert_eq!(response_body.api_keys[0].prefix, "tdk_abcdefgh");
+        assert!(response_body.api_keys[0].revoked_at.is_some());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    server::{
+        domain::{api_keys::ApiKey, errors::ErrorResponse},
+        errors::AppError,
+        extractors::auth_user::AuthUser,
+        openapi::AUTH_TAG,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+/// Revoke API key
+///
+/// Revoke the API key of the user with given id, the key is not accepted anymore. Revoking a key again keeps the time it was first revoked.
+#[utoipa::path(
+    delete,
+    path = "/keys/{id}",
+    tag = AUTH_TAG,
+    responses(
+        (status = 200, description = "API key revoked", body = ApiKey),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 404, description = "API key not found"),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "API key id"),
+    ),
+    security(
+        ("basic_auth" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn api_keys_revoke(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    AuthUser(user, _): AuthUser,
+) -> Result<Json<ApiKey>, AppError> {
+    let key_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+
+    let db_api_key = state.db.revoke_api_key(&user, key_id).await?;
+    tracing::info!("User {} revoked api key {}", user, key_id);
+
+    Ok(Json(ApiKey::try_from(db_api_key)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbApiKey;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::api_keys::ApiKey;
+    use crate::server::handlers::api_keys_revoke::api_keys_revoke;
+    use crate::test_utils::{init_router, read_response_body, test_delete};
+    use axum::http::StatusCode;
+    use axum::routing::delete;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_api_keys_revoke() {
+        let id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_revoke_api_key()
+            .withf(move |owner, revoke_id| owner == "user" && *revoke_id == id)
+            .returning(|owner, id| {
+                Ok(DbApiKey {
+                    id,
+                    owner: owner.to_string(),
+                    name: "Nightly import".to_string(),
+                    role: "editor".to_string(),
+                    prefix: "tdk_abcdefgh".to_string(),
+                    created_at: Utc::now(),
+                    revoked_at: Some(Utc::now()),
+                })
+            });
+        let app = init_router(mock_db, "/auth/keys/{id}", delete(api_keys_revoke)).await;
+
+        let response = test_delete(app, &format!("/auth/keys/{}", id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let api_key: ApiKey = read_response_body(response).await;
+        assert_eq!(api_key.id, id.to_string());
+        assert!(api_key.revoked_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_revoke_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_revoke_api_key()
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/auth/keys/{id}", delete(api_keys_revoke)).await;
+
+        let response = test_delete(app, &format!("/auth/keys/{}", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
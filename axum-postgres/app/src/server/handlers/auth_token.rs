@@ -0,0 +1,86 @@
+use crate::{
+    server::{
+        domain::{auth::TokenResponse, errors::ErrorResponse},
+        errors::AppError,
+        extractors::auth_basic::AuthBasic,
+        openapi::AUTH_TAG,
+    },
+    SharedState,
+};
+use axum::{extract::State, Json};
+
+/// Create access token
+///
+/// Exchanges Basic credentials for a signed access token with the role of the user, which expires after `expires_in` seconds.
+#[utoipa::path(
+    post,
+    path = "/token",
+    tag = AUTH_TAG,
+    responses(
+        (status = 200, description = "Access token created", body = TokenResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid authentication header".to_string() })),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = [])
+    )
+)]
+pub async fn auth_token(
+    State(state): State<SharedState>,
+    AuthBasic(user, role): AuthBasic,
+) -> Result<Json<TokenResponse>, AppError> {
+    let access_token = state.tokens.issue(&user, role)?;
+    tracing::info!("Issued access token for user {}", user);
+
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: state.tokens.expiration().as_secs(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::auth::TokenResponse;
+    use crate::server::handlers::auth_token::auth_token;
+    use crate::server::roles::Role;
+    use crate::test_utils::{init_router, read_response_body, test_authenticated, test_tokens};
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use base64::Engine;
+
+    #[tokio::test]
+    async fn test_auth_token() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/auth/token", post(auth_token)).await;
+
+        let header = &format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode("user:pass".as_bytes())
+        );
+        let response = test_authenticated(app, "/auth/token", "POST", header).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TokenResponse = read_response_body(response).await;
+        assert_eq!(response_body.token_type, "Bearer");
+        assert_eq!(response_body.expires_in, 60);
+        let claims = test_tokens().validate(&response_body.access_token).unwrap();
+        assert_eq!(claims.sub, "user");
+        assert_eq!(claims.role, Role::Editor);
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_invalid_credentials() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/auth/token", post(auth_token)).await;
+
+        let header = &format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode("user:invalid".as_bytes())
+        );
+        let response = test_authenticated(app, "/auth/token", "POST", header).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
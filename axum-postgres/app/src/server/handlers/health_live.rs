@@ -0,0 +1,39 @@
+use crate::server::{domain::health::HealthResponse, openapi::HEALTH_TAG};
+use axum::Json;
+
+/// Liveness
+///
+/// Succeeds while the process is running, also while it drains requests before shutting down.
+#[utoipa::path(
+    get,
+    path = "/live",
+    tag = HEALTH_TAG,
+    responses(
+        (status = 200, description = "Process is running", body = HealthResponse),
+    )
+)]
+pub async fn health_live() -> Json<HealthResponse> {
+    Json(HealthResponse::ok(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::health::HealthResponse;
+    use crate::server::handlers::health_live::health_live;
+    use crate::test_utils::{init_router, read_response_body, test_get};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+
+    #[tokio::test]
+    async fn test_health_live() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/health/live", get(health_live)).await;
+
+        let response = test_get(app, "/health/live").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: HealthResponse = read_response_body(response).await;
+        assert_eq!(response_body.status, "ok");
+    }
+}
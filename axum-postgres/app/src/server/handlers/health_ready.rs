@@ -0,0 +1,134 @@
+use crate::{
+    server::{
+        domain::health::{HealthResponse, PoolHealth},
+        openapi::HEALTH_TAG,
+    },
+    SharedState,
+};
+use axum::{extract::State, http::StatusCode, Json};
+use std::{sync::atomic::Ordering, time::Duration};
+
+/// Readiness fails instead of waiting for the database longer than this.
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Readiness
+///
+/// Succeeds if the database is reachable, with the connections of its pool. Fails with 503 if the database does not
+/// respond within a second and once the instance is shutting down, so load balancers stop sending requests to it.
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = HEALTH_TAG,
+    responses(
+        (status = 200, description = "Instance is ready for requests", body = HealthResponse),
+        (status = 503, description = "Instance is not ready for requests", body = HealthResponse,
+            example = json!(HealthResponse::unavailable("shutting down"))),
+    )
+)]
+pub async fn health_ready(State(state): State<SharedState>) -> (StatusCode, Json<HealthResponse>) {
+    if state.shutting_down.load(Ordering::Relaxed) {
+        return unavailable("shutting down");
+    }
+
+    match tokio::time::timeout(PING_TIMEOUT, state.db.ping()).await {
+        Ok(Ok(status)) => {
+            let pool = status.map(PoolHealth::from);
+            if pool.as_ref().is_some_and(|pool| pool.saturated) {
+                tracing::warn!("Database connection pool is saturated");
+            }
+            (StatusCode::OK, Json(HealthResponse::ok(pool)))
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("Database ping failed: {:?}", e);
+            unavailable("database is not reachable")
+        }
+        Err(_) => {
+            tracing::warn!("Database ping timed out after {:?}", PING_TIMEOUT);
+            unavailable("database ping timed out")
+        }
+    }
+}
+
+fn unavailable(reason: &str) -> (StatusCode, Json<HealthResponse>) {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(HealthResponse::unavailable(reason)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbPoolStatus;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::health::HealthResponse;
+    use crate::server::handlers::health_ready::health_ready;
+    use crate::test_utils::{read_response_body, test_get, test_state};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_health_ready() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_ping().returning(|| {
+            Ok(Some(DbPoolStatus {
+                size: 5,
+                idle: 0,
+                max_size: 5,
+            }))
+        });
+        let app = Router::new()
+            .route("/health/ready", get(health_ready))
+            .with_state(test_state(mock_db));
+
+        let response = test_get(app, "/health/ready").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: HealthResponse = read_response_body(response).await;
+        assert_eq!(response_body.status, "ok");
+        let pool = response_body.pool.unwrap();
+        assert_eq!((pool.size, pool.idle, pool.max_size), (5, 0, 5));
+        assert!(pool.saturated);
+    }
+
+    #[tokio::test]
+    async fn test_health_ready_database_error() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_ping().returning(|| {
+            Err(DatabaseError::Internal(anyhow::anyhow!(
+                "connection refused"
+            )))
+        });
+        let app = Router::new()
+            .route("/health/ready", get(health_ready))
+            .with_state(test_state(mock_db));
+
+        let response = test_get(app, "/health/ready").await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response_body: HealthResponse = read_response_body(response).await;
+        assert_eq!(response_body.status, "unavailable");
+        assert_eq!(
+            response_body.reason.as_deref(),
+            Some("database is not reachable")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_ready_shutting_down() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_ping().never();
+        let state = test_state(mock_db);
+        state.shutting_down.store(true, Ordering::Relaxed);
+        let app = Router::new()
+            .route("/health/ready", get(health_ready))
+            .with_state(state);
+
+        let response = test_get(app, "/health/ready").await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response_body: HealthResponse = read_response_body(response).await;
+        assert_eq!(response_body.reason.as_deref(), Some("shutting down"));
+    }
+}
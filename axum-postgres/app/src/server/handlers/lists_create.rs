@@ -0,0 +1,107 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            lists::{List, NewList},
+        },
+        errors::AppError,
+        extractors::{request_json::ValidatedJson, require_role::RequireRole},
+        openapi::LISTS_TAG,
+        roles::Editor,
+    },
+    SharedState,
+};
+use axum::{extract::State, http::StatusCode, Json};
+
+/// Create new list
+///
+/// Creates an empty list of the user, Todo items are added to it with their `list_id`.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = LISTS_TAG,
+    request_body = NewList,
+    responses(
+        (status = 201, description = "List created successfully", body = List),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "name: length must be between 1 and 200".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
+    )
+)]
+pub async fn lists_create(
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    ValidatedJson(input): ValidatedJson<NewList>,
+) -> Result<(StatusCode, Json<List>), AppError> {
+    let db_list = state.db.insert_list(&user, input.into()).await?;
+    Ok((StatusCode::CREATED, Json(db_list.into())))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbList;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::domain::lists::{List, NewList};
+    use crate::server::handlers::lists_create::lists_create;
+    use crate::test_utils::{init_router, read_response_body, test_post};
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_lists_create() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_list()
+            .withf(|owner, list| owner == "user" && list.name == "Groceries")
+            .returning(|owner, list| {
+                Ok(DbList {
+                    id: Uuid::new_v4(),
+                    owner: owner.to_string(),
+                    name: list.name,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    todo_count: 0,
+                    completed_count: 0,
+                })
+            });
+        let app = init_router(mock_db, "/lists", post(lists_create)).await;
+
+        let new_list = NewList {
+            name: "Groceries".to_string(),
+        };
+        let response = test_post(app, "/lists", new_list).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let list: List = read_response_body(response).await;
+        assert_eq!(list.name, "Groceries");
+        assert_eq!(list.todo_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_lists_create_invalid_name() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/lists", post(lists_create)).await;
+
+        let new_list = NewList {
+            name: "".to_string(),
+        };
+        let response = test_post(app, "/lists", new_list).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "name: length must be between 1 and 200"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    server::{
+        domain::{errors::ErrorResponse, lists::DeleteListQuery},
+        errors::AppError,
+        extractors::{request_query::ValidatedQuery, require_role::RequireRole},
+        openapi::LISTS_TAG,
+        roles::Editor,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+/// Delete list by id
+///
+/// Delete the list with given id together with its Todo items. With `move_to` the items are moved to that list instead,
+/// which increments their version. Fails with 422 if there is no list `move_to`.
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = LISTS_TAG,
+    responses(
+        (status = 200, description = "List deleted successfully"),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "cannot move the todos to the deleted list".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "List not found"),
+        (status = 422, description = "List to move the todos to not found", body = ErrorResponse),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "List id"),
+        DeleteListQuery,
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
+    )
+)]
+pub async fn lists_delete(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    ValidatedQuery(query): ValidatedQuery<DeleteListQuery>,
+) -> Result<StatusCode, AppError> {
+    let list_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+    if query.move_to == Some(list_id) {
+        return Err(AppError::BadRequest(
+            "cannot move the todos to the deleted list".to_string(),
+        ));
+    }
+
+    state.db.remove_list(&user, list_id, query.move_to).await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::handlers::lists_delete::lists_delete;
+    use crate::test_utils::{init_router, read_response_body, test_delete};
+    use axum::http::StatusCode;
+    use axum::routing::delete;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_lists_delete() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_remove_list()
+            .withf(|owner, _, move_to| owner == "user" && move_to.is_none())
+            .returning(|_, _, _| Ok(()));
+        let app = init_router(mock_db, "/lists/{id}", delete(lists_delete)).await;
+
+        let response = test_delete(app, &format!("/lists/{}", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_lists_delete_move_to() {
+        let move_to = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_remove_list()
+            .withf(move |_, _, target| *target == Some(move_to))
+            .returning(|_, _, move_to| {
+                Err(DatabaseError::ListNotFound {
+                    id: move_to.unwrap(),
+                })
+            });
+        let app = init_router(mock_db, "/lists/{id}", delete(lists_delete)).await;
+
+        let uri = format!("/lists/{}?move_to={}", Uuid::new_v4(), move_to);
+        let response = test_delete(app, &uri).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_lists_delete_move_to_itself() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/lists/{id}", delete(lists_delete)).await;
+
+        let id = Uuid::new_v4();
+        let response = test_delete(app, &format!("/lists/{}?move_to={}", id, id)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "cannot move the todos to the deleted list"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lists_delete_invalid_move_to() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/lists/{id}", delete(lists_delete)).await;
+
+        let uri = format!("/lists/{}?move_to=invalid", Uuid::new_v4());
+        let response = test_delete(app, &uri).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "failed to read query");
+    }
+}
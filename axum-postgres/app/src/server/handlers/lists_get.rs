@@ -0,0 +1,119 @@
+use crate::{
+    server::{
+        domain::{errors::ErrorResponse, lists::List},
+        errors::AppError,
+        extractors::require_role::RequireRole,
+        openapi::LISTS_TAG,
+        roles::Viewer,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+/// Get list by id
+///
+/// Get the list with given id with the number of its Todo items and of the completed ones.
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = LISTS_TAG,
+    responses(
+        (status = 200, description = "List found", body = List),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "List not found"),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "List id"),
+    ),
+    security(
+        ("basic_auth" = ["viewer"]),
+        ("bearer_auth" = ["viewer"]),
+        ("api_key" = ["viewer"])
+    )
+)]
+pub async fn lists_get(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Viewer>,
+) -> Result<Json<List>, AppError> {
+    let list_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+
+    let list = state.db.get_list(&user, list_id).await?;
+    Ok(Json(list.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbList;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::domain::lists::List;
+    use crate::server::handlers::lists_get::lists_get;
+    use crate::test_utils::{init_router, read_response_body, test_get};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_lists_get() {
+        let id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_list()
+            .withf(move |owner, get_id| owner == "user" && *get_id == id)
+            .returning(|owner, id| {
+                Ok(DbList {
+                    id,
+                    owner: owner.to_string(),
+                    name: "Groceries".to_string(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    todo_count: 2,
+                    completed_count: 2,
+                })
+            });
+        let app = init_router(mock_db, "/lists/{id}", get(lists_get)).await;
+
+        let response = test_get(app, &format!("/lists/{}", id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let list: List = read_response_body(response).await;
+        assert_eq!(list.id, id.to_string());
+        assert_eq!(list.completed_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_lists_get_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_list()
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/lists/{id}", get(lists_get)).await;
+
+        let response = test_get(app, &format!("/lists/{}", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_lists_get_invalid_id() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/lists/{id}", get(lists_get)).await;
+
+        let response = test_get(app, "/lists/invalid").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "id is not valid uuid: invalid");
+    }
+}
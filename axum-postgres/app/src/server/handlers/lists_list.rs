@@ -0,0 +1,90 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            lists::{List, ListsResponse},
+        },
+        errors::AppError,
+        extractors::require_role::RequireRole,
+        openapi::LISTS_TAG,
+        roles::Viewer,
+    },
+    SharedState,
+};
+use axum::{extract::State, Json};
+
+/// List lists
+///
+/// List the lists of the user, oldest first, with the number of their Todo items and of the completed ones.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = LISTS_TAG,
+    responses(
+        (status = 200, description = "List lists successfully", body = ListsResponse),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["viewer"]),
+        ("bearer_auth" = ["viewer"]),
+        ("api_key" = ["viewer"])
+    )
+)]
+pub async fn lists_list(
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Viewer>,
+) -> Result<Json<ListsResponse>, AppError> {
+    let lists = state
+        .db
+        .get_lists(&user)
+        .await?
+        .into_iter()
+        .map(List::from)
+        .collect();
+
+    Ok(Json(ListsResponse { lists }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbList;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::lists::ListsResponse;
+    use crate::server::handlers::lists_list::lists_list;
+    use crate::test_utils::{init_router, read_response_body, test_get};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_lists_list() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_lists()
+            .withf(|owner| owner == "user")
+            .returning(|owner| {
+                Ok(vec![DbList {
+                    id: Uuid::new_v4(),
+                    owner: owner.to_string(),
+                    name: "Groceries".to_string(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    todo_count: 3,
+                    completed_count: 1,
+                }])
+            });
+        let app = init_router(mock_db, "/lists", get(lists_list)).await;
+
+        let response = test_get(app, "/lists").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: ListsResponse = read_response_body(response).await;
+        assert_eq!(response_body.lists.len(), 1);
+        assert_eq!(response_body.lists[0].name, "Groceries");
+        assert_eq!(response_body.lists[0].todo_count, 3);
+        assert_eq!(response_body.lists[0].completed_count, 1);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            todos::{TodosQuery, TodosResponse},
+        },
+        errors::AppError,
+        extractors::{request_query::ValidatedQuery, require_role::RequireRole},
+        handlers::todos_list::list_todos,
+        openapi::LISTS_TAG,
+        roles::Viewer,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+use uuid::Uuid;
+
+/// List Todo items of a list
+///
+/// List the Todo items of the list with given id one page at a time, with the same query parameters as listing all todos.
+#[utoipa::path(
+    get,
+    path = "/{id}/todos",
+    tag = LISTS_TAG,
+    params(
+        ("id" = String, Path, description = "List id"),
+        TodosQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched page"),
+    ),
+    responses(
+        (status = 200, description = "List todos successfully", body = TodosResponse,
+            headers(("ETag" = String, description = "Version of the page"))),
+        (status = 304, description = "Page not modified since the given ETag"),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "limit: must be between 1 and 100".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "List not found"),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["viewer"]),
+        ("bearer_auth" = ["viewer"]),
+        ("api_key" = ["viewer"])
+    )
+)]
+pub async fn lists_todos(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Viewer>,
+    headers: HeaderMap,
+    ValidatedQuery(query): ValidatedQuery<TodosQuery>,
+) -> Result<Response, AppError> {
+    let list_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+
+    // an empty page of a missing list would look like an empty list
+    state.db.get_list(&user, list_id).await?;
+    list_todos(&state, &user, Some(list_id), &headers, query).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::{DbList, DbTodo};
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::todos::TodosResponse;
+    use crate::server::handlers::lists_todos::lists_todos;
+    use crate::test_utils::{db_todo, init_router, read_response_body, test_get};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_lists_todos() {
+        let list_id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get_list().returning(|owner, id| {
+            Ok(DbList {
+                id,
+                owner: owner.to_string(),
+                name: "Groceries".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                todo_count: 1,
+                completed_count: 0,
+            })
+        });
+        mock_db
+            .expect_get_values()
+            .withf(move |owner, query| owner == "user" && query.list_id == Some(list_id))
+            .returning(|owner, query| {
+                Ok(vec![DbTodo {
+                    list_id: query.list_id,
+                    ..db_todo(Uuid::new_v4(), owner, "Buy milk")
+                }])
+            });
+        let app = init_router(mock_db, "/lists/{id}/todos", get(lists_todos)).await;
+
+        let response = test_get(app, &format!("/lists/{}/todos", list_id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TodosResponse = read_response_body(response).await;
+        assert_eq!(response_body.todos.len(), 1);
+        assert_eq!(response_body.todos[0].list_id, Some(list_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_lists_todos_list_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_list()
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/lists/{id}/todos", get(lists_todos)).await;
+
+        let response = test_get(app, &format!("/lists/{}/todos", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            lists::{List, UpdateList},
+        },
+        errors::AppError,
+        extractors::{request_json::ValidatedJson, require_role::RequireRole},
+        openapi::LISTS_TAG,
+        roles::Editor,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use uuid::Uuid;
+
+/// Update list by id
+///
+/// Rename the list with given id, fields that are missing are kept.
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = LISTS_TAG,
+    request_body = UpdateList,
+    responses(
+        (status = 200, description = "List updated successfully", body = List),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "name: length must be between 1 and 200".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "List not found"),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "List id"),
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
+    )
+)]
+pub async fn lists_update(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    ValidatedJson(input): ValidatedJson<UpdateList>,
+) -> Result<Json<List>, AppError> {
+    let list_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+
+    let list = state.db.update_list(&user, list_id, input.into()).await?;
+    Ok(Json(list.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbList;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::lists::List;
+    use crate::server::handlers::lists_update::lists_update;
+    use crate::test_utils::{init_router, read_response_body, test_with_headers};
+    use axum::http::StatusCode;
+    use axum::routing::patch;
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_lists_update() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_update_list()
+            .withf(|owner, _, list| owner == "user" && list.name.as_deref() == Some("Shopping"))
+            .returning(|owner, id, list| {
+                Ok(DbList {
+                    id,
+                    owner: owner.to_string(),
+                    name: list.name.unwrap(),
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    todo_count: 0,
+                    completed_count: 0,
+                })
+            });
+        let app = init_router(mock_db, "/lists/{id}", patch(lists_update)).await;
+
+        let uri = format!("/lists/{}", Uuid::new_v4());
+        let body = json!({ "name": "Shopping" });
+        let response = test_with_headers(app, "PATCH", &uri, &[], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let list: List = read_response_body(response).await;
+        assert_eq!(list.name, "Shopping");
+    }
+
+    #[tokio::test]
+    async fn test_lists_update_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_update_list()
+            .returning(|_, id, _| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/lists/{id}", patch(lists_update)).await;
+
+        let uri = format!("/lists/{}", Uuid::new_v4());
+        let body = json!({ "name": "Shopping" });
+        let response = test_with_headers(app, "PATCH", &uri, &[], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
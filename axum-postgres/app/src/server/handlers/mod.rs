@@ -1,5 +1,24 @@
+pub mod admin_todos_list;
+pub mod admin_todos_purge;
+pub mod api_keys_create;
+pub mod api_keys_list;
+pub mod api_keys_revoke;
+pub mod auth_token;
+pub mod health_live;
+pub mod health_ready;
+pub mod lists_create;
+pub mod lists_delete;
+pub mod lists_get;
+pub mod lists_list;
+pub mod lists_todos;
+pub mod lists_update;
 pub mod protected;
+pub mod todos_batch;
 pub mod todos_create;
 pub mod todos_delete;
+pub mod todos_get;
 pub mod todos_list;
+pub mod todos_overdue;
+pub mod todos_patch;
+pub mod todos_replace;
 pub mod todos_update;
@@ -1,14 +1,14 @@
 use crate::server::{
     domain::{common::MessageResponse, errors::ErrorResponse},
     errors::AppError,
-    extractors::auth_basic::AuthBasic,
+    extractors::auth_user::AuthUser,
     openapi::PROTECTED_TAG,
 };
 use axum::Json;
 
 /// Access protected route
 ///
-/// Access a protected route that requires authentication.
+/// Access a protected route that requires authentication with Basic credentials or an access token.
 #[utoipa::path(
     get,
     path = "/",
@@ -20,10 +20,11 @@ use axum::Json;
         (status = 500, description = "Internal error", body = ErrorResponse)
     ),
     security(
-        ("basic_auth" = [])
+        ("basic_auth" = []),
+        ("bearer_auth" = [])
     )
 )]
-pub async fn protected(AuthBasic(user): AuthBasic) -> Result<Json<MessageResponse>, AppError> {
+pub async fn protected(AuthUser(user, _): AuthUser) -> Result<Json<MessageResponse>, AppError> {
     tracing::info!("User {} accessed protected route", user);
 
     Ok(Json(MessageResponse {
@@ -33,17 +34,17 @@ pub async fn protected(AuthBasic(user): AuthBasic) -> Result<Json<MessageRespons
 
 #[cfg(test)]
 mod tests {
-    use crate::datasources::database::MockDatabase;
+    use crate::datasources::database::MockTodoRepository;
     use crate::server::domain::common::MessageResponse;
     use crate::server::handlers::protected::protected;
-    use crate::test_utils::{init_router, read_response_body, test_authenticated};
+    use crate::test_utils::{init_router, read_response_body, test_authenticated, test_bearer};
     use axum::http::StatusCode;
     use axum::routing::get;
     use base64::Engine;
 
     #[tokio::test]
     async fn test_protected() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/protected", get(protected)).await;
 
         let header = &format!(
@@ -56,4 +57,16 @@ mod tests {
         let response_body: MessageResponse = read_response_body(response).await;
         assert_eq!(response_body.message, "Hello, user!");
     }
+
+    #[tokio::test]
+    async fn test_protected_bearer() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/protected", get(protected)).await;
+
+        let response = test_authenticated(app, "/protected", "GET", &test_bearer("user")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: MessageResponse = read_response_body(response).await;
+        assert_eq!(response_body.message, "Hello, user!");
+    }
 }
@@ -0,0 +1,378 @@
+use crate::{
+    datasources::database::{
+        models::{DbBatchOperation, DbBatchResult},
+        DatabaseError,
+    },
+    server::{
+        domain::{
+            batch::{BatchOperation, BatchRequest, BatchResponse, BatchResult},
+            errors::ErrorResponse,
+        },
+        errors::AppError,
+        extractors::{request_json::ValidatedJson, require_role::RequireRole},
+        openapi::TODO_TAG,
+        roles::Editor,
+    },
+    SharedState,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Create, update and delete Todo items in one batch
+///
+/// Applies all operations in the given order, either all of them or none. If an operation fails the
+/// response has its status, 404, 412 or 422, and the other operations are rolled back with status 424.
+#[utoipa::path(
+    post,
+    path = "/batch",
+    tag = TODO_TAG,
+    request_body = BatchRequest,
+    responses(
+        (status = 200, description = "All operations applied", body = BatchResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "operations[0]: id is not valid uuid: 1".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "Todo of an operation not found, nothing applied", body = BatchResponse),
+        (status = 412, description = "Todo of an operation does not have the given version, nothing applied", body = BatchResponse),
+        (status = 422, description = "List of an operation not found, nothing applied", body = BatchResponse),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
+    )
+)]
+pub async fn todos_batch(
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    ValidatedJson(input): ValidatedJson<BatchRequest>,
+) -> Result<Response, AppError> {
+    let ids: Vec<Option<String>> = input
+        .operations
+        .iter()
+        .map(|operation| match operation {
+            BatchOperation::Create(_) => None,
+            BatchOperation::Update(update_todo) => Some(update_todo.id.clone()),
+            BatchOperation::Delete(delete_todo) => Some(delete_todo.id.clone()),
+        })
+        .collect();
+    let operations = input
+        .operations
+        .into_iter()
+        .enumerate()
+        .map(|(index, operation)| db_operation(index, operation))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match state.db.batch(&user, operations).await {
+        Ok(db_results) => {
+            let results = db_results.into_iter().map(batch_result).collect();
+            Ok(Json(BatchResponse { results }).into_response())
+        }
+        Err(DatabaseError::Batch { index, source }) => {
+            warn!("Batch operation {} failed: {}", index, source);
+            let (status, error) = match *source {
+                DatabaseError::NotFound { .. } => (StatusCode::NOT_FOUND, "not found"),
+                DatabaseError::ListNotFound { .. } => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "list not found")
+                }
+                DatabaseError::VersionMismatch { .. } => {
+                    (StatusCode::PRECONDITION_FAILED, "precondition failed")
+                }
+                source => return Err(source.into()),
+            };
+
+            let results = ids
+                .into_iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    let (status, error) = if i == index {
+                        (status, error.to_string())
+                    } else {
+                        (
+                            StatusCode::FAILED_DEPENDENCY,
+                            format!("operation {} failed", index),
+                        )
+                    };
+                    BatchResult {
+                        status: status.as_u16(),
+                        id,
+                        todo: None,
+                        error: Some(error),
+                    }
+                })
+                .collect();
+            Ok((status, Json(BatchResponse { results })).into_response())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn db_operation(index: usize, operation: BatchOperation) -> Result<DbBatchOperation, AppError> {
+    let parse_id = |id: &str| {
+        Uuid::parse_str(id).map_err(|_| {
+            AppError::BadRequest(format!(
+                "operations[{}]: id is not valid uuid: {}",
+                index, id
+            ))
+        })
+    };
+    match operation {
+        BatchOperation::Create(new_todo) => Ok(DbBatchOperation::Insert(new_todo.into())),
+        BatchOperation::Update(update_todo) => {
+            let id = parse_id(&update_todo.id)?;
+            let todo = update_todo.todo;
+            if todo.text.is_none()
+                && todo.completed.is_none()
+                && todo.list_id.is_none()
+                && todo.tags.is_none()
+                && todo.due_at.is_none()
+                && todo.priority.is_none()
+            {
+                return Err(AppError::BadRequest(format!(
+                    "operations[{}]: either text, completed, list_id, tags, due_at or priority must be present",
+                    index
+                )));
+            }
+            Ok(DbBatchOperation::Update {
+                id,
+                todo: todo.into(),
+                expected_version: update_todo.version,
+            })
+        }
+        BatchOperation::Delete(delete_todo) => Ok(DbBatchOperation::Remove {
+            id: parse_id(&delete_todo.id)?,
+            expected_version: delete_todo.version,
+        }),
+    }
+}
+
+fn batch_result(db_result: DbBatchResult) -> BatchResult {
+    let (status, id, todo) = match db_result {
+        DbBatchResult::Inserted(db_todo) => (StatusCode::CREATED, None, Some(db_todo.into())),
+        DbBatchResult::Updated(db_todo) => (
+            StatusCode::OK,
+            Some(db_todo.id.to_string()),
+            Some(db_todo.into()),
+        ),
+        DbBatchResult::Removed(id) => (StatusCode::OK, Some(id.to_string()), None),
+    };
+    BatchResult {
+        status: status.as_u16(),
+        id,
+        todo,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::{DbBatchOperation, DbBatchResult, DbTodo};
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::batch::BatchResponse;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::handlers::todos_batch::todos_batch;
+    use crate::test_utils::{db_todo, init_router, read_response_body, test_post};
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_todos_batch() {
+        let update_id = Uuid::new_v4();
+        let delete_id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_batch()
+            .withf(move |_, operations| {
+                matches!(
+                    operations.as_slice(),
+                    [
+                        DbBatchOperation::Insert(new_todo),
+                        DbBatchOperation::Update { id: updated, todo, expected_version: Some(1) },
+                        DbBatchOperation::Remove { id: removed, expected_version: None },
+                    ] if new_todo.text == "New todo"
+                        && *updated == update_id
+                        && todo.completed == Some(true)
+                        && todo.text.is_none()
+                        && *removed == delete_id
+                )
+            })
+            .returning(move |_, _| {
+                Ok(vec![
+                    DbBatchResult::Inserted(db_todo(Uuid::new_v4(), "user", "New todo")),
+                    DbBatchResult::Updated(DbTodo {
+                        completed: true,
+                        version: 2,
+                        ..db_todo(update_id, "user", "Updated todo")
+                    }),
+                    DbBatchResult::Removed(delete_id),
+                ])
+            });
+        let app = init_router(mock_db, "/todos/batch", post(todos_batch)).await;
+
+        let body = json!({
+            "operations": [
+                { "op": "create", "text": "New todo" },
+                { "op": "update", "id": update_id, "completed": true, "version": 1 },
+                { "op": "delete", "id": delete_id },
+            ]
+        });
+        let response = test_post(app, "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: BatchResponse = read_response_body(response).await;
+        let statuses: Vec<u16> = response_body.results.iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![201, 200, 200]);
+        assert_eq!(
+            response_body.results[0].todo.as_ref().unwrap().text,
+            "New todo"
+        );
+        assert!(response_body.results[1].todo.as_ref().unwrap().completed);
+        assert_eq!(
+            response_body.results[2].id.as_deref(),
+            Some(delete_id.to_string().as_str())
+        );
+        assert!(response_body.results[2].todo.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_todos_batch_rolled_back() {
+        let id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_batch().returning(move |_, _| {
+            Err(DatabaseError::Batch {
+                index: 1,
+                source: Box::new(DatabaseError::VersionMismatch {
+                    id,
+                    expected_version: 1,
+                }),
+            })
+        });
+        let app = init_router(mock_db, "/todos/batch", post(todos_batch)).await;
+
+        let body = json!({
+            "operations": [
+                { "op": "create", "text": "New todo" },
+                { "op": "delete", "id": id, "version": 1 },
+                { "op": "update", "id": id, "text": "Updated todo" },
+            ]
+        });
+        let response = test_post(app, "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response_body: BatchResponse = read_response_body(response).await;
+        let statuses: Vec<u16> = response_body.results.iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![424, 412, 424]);
+        assert_eq!(
+            response_body.results[1].error.as_deref(),
+            Some("precondition failed")
+        );
+        assert_eq!(
+            response_body.results[2].error.as_deref(),
+            Some("operation 1 failed")
+        );
+        assert!(response_body.results.iter().all(|r| r.todo.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_todos_batch_not_found() {
+        let id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_batch().returning(move |_, _| {
+            Err(DatabaseError::Batch {
+                index: 0,
+                source: Box::new(DatabaseError::NotFound { id }),
+            })
+        });
+        let app = init_router(mock_db, "/todos/batch", post(todos_batch)).await;
+
+        let body = json!({ "operations": [{ "op": "delete", "id": id }] });
+        let response = test_post(app, "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response_body: BatchResponse = read_response_body(response).await;
+        assert_eq!(response_body.results[0].status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_todos_batch_list_not_found() {
+        let list_id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_batch().returning(move |_, _| {
+            Err(DatabaseError::Batch {
+                index: 1,
+                source: Box::new(DatabaseError::ListNotFound { id: list_id }),
+            })
+        });
+        let app = init_router(mock_db, "/todos/batch", post(todos_batch)).await;
+
+        let body = json!({
+            "operations": [
+                { "op": "create", "text": "New todo" },
+                { "op": "update", "id": Uuid::new_v4(), "list_id": list_id },
+            ]
+        });
+        let response = test_post(app, "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response_body: BatchResponse = read_response_body(response).await;
+        let statuses: Vec<u16> = response_body.results.iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![424, 422]);
+        assert_eq!(
+            response_body.results[1].error.as_deref(),
+            Some("list not found")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todos_batch_invalid() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos/batch", post(todos_batch)).await;
+
+        let response = test_post(app.clone(), "/todos/batch", json!({ "operations": [] })).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = json!({ "operations": [{ "op": "create", "text": "" }] });
+        let response = test_post(app.clone(), "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert!(response_body
+            .error
+            .contains("length must be between 1 and 200"));
+
+        let body = json!({ "operations": [{ "op": "delete", "id": "invalid" }] });
+        let response = test_post(app.clone(), "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "operations[0]: id is not valid uuid: invalid"
+        );
+
+        let body = json!({ "operations": [{ "op": "update", "id": Uuid::new_v4() }] });
+        let response = test_post(app.clone(), "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "operations[0]: either text, completed, list_id, tags, due_at or priority must be present"
+        );
+
+        let body = json!({ "operations": [{ "op": "archive" }] });
+        let response = test_post(app, "/todos/batch", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
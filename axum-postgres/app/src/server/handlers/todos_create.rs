@@ -3,85 +3,235 @@ use crate::{
     server::{
         domain::{
             errors::ErrorResponse,
+            etag::todo_etag,
             todos::{NewTodo, Todo},
         },
         errors::AppError,
-        extractors::request_json::ValidatedJson,
+        extractors::{
+            idempotency_key::IdempotencyKey, request_json::ValidatedJson, require_role::RequireRole,
+        },
+        idempotency::{self, Reservation},
         openapi::TODO_TAG,
+        roles::Editor,
     },
     SharedState,
 };
-use axum::{extract::State, http::StatusCode, Json};
+use anyhow::Context;
+use axum::{
+    extract::State,
+    http::{HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::TypedHeader;
+use tracing::Instrument;
+
+/// Header of responses replayed for a retried request with an `Idempotency-Key`.
+const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
 
 /// Create new Todo
 ///
-/// Tries to create a new Todo item to in-memory storage or fails with 409 conflict if already exists.
+/// Creates a new Todo item of the user.
+///
+/// Requests with an `Idempotency-Key` header can be retried safely, e.g. after a timeout. The todo is created once
+/// and retries with the same key and body get the original response, with the `Idempotent-Replayed: true` header,
+/// until the key expires. Reusing a key with another body fails with 422.
 #[utoipa::path(
     post,
     path = "/",
     tag = TODO_TAG,
     request_body = NewTodo,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Key of the request chosen by the client, up to 255 characters"),
+    ),
     responses(
-        (status = 201, description = "Todo item created successfully", body = Todo),
+        (status = 201, description = "Todo item created successfully", body = Todo,
+            headers(
+                ("ETag" = String, description = "Version of the created todo"),
+                ("Idempotent-Replayed" = Option<String>, description = "`true` if the response is replayed for a retried request")
+            )),
         (status = 400, description = "Bad request", body = ErrorResponse, 
             example = json!(ErrorResponse { error: "text: length must be between 1 and 200".to_string() })),
-        (status = 404, description = "Todo not found"),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 409, description = "Request with the Idempotency-Key is in progress", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "request with the Idempotency-Key is in progress".to_string() })),
+        (status = 422, description = "Idempotency-Key was used with another request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "Idempotency-Key was already used with another request".to_string() })),
         (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
     )
 )]
 pub async fn todos_create(
     State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    IdempotencyKey(key): IdempotencyKey,
     ValidatedJson(input): ValidatedJson<NewTodo>,
-) -> Result<(StatusCode, Json<Todo>), AppError> {
+) -> Result<Response, AppError> {
+    let Some(key) = key else {
+        let todo = create_todo(&state, &user, input).await?;
+        return Ok(todo_response(StatusCode::CREATED, todo));
+    };
+
+    let request_hash = idempotency::request_hash(&input)?;
+    if let Reservation::Replay { status, body } =
+        idempotency::reserve(&state, &user, &key, request_hash).await?
+    {
+        tracing::info!("Replaying response of Idempotency-Key {}", key);
+        let todo: Todo = serde_json::from_str(&body).context("stored response is not a todo")?;
+        let mut response = todo_response(status, todo);
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        return Ok(response);
+    }
+
+    // the todo is created even if the request is cancelled, e.g. by the timeout, so that retries
+    // get the response instead of a released key
+    let todo = tokio::spawn(
+        async move {
+            let result = create_todo(&state, &user, input).await;
+            let stored = match &result {
+                Ok(todo) => {
+                    idempotency::complete(&state, &user, &key, StatusCode::CREATED, todo).await
+                }
+                Err(_) => idempotency::release(&state, &user, &key).await,
+            };
+            if let Err(e) = stored {
+                tracing::error!("Failed to store Idempotency-Key {}: {:?}", key, e);
+            }
+            result
+        }
+        .in_current_span(),
+    )
+    .await
+    .context("creating todo failed")??;
+    Ok(todo_response(StatusCode::CREATED, todo))
+}
+
+async fn create_todo(state: &SharedState, user: &str, input: NewTodo) -> Result<Todo, AppError> {
     let new_todo: DbNewTodo = input.into();
-    let db_todo = state.db.insert(new_todo).await?;
+    let db_todo = state.db.insert(user, new_todo).await?;
+    Ok(db_todo.into())
+}
 
-    let todo: Todo = db_todo.into();
-    Ok((StatusCode::CREATED, Json(todo)))
+fn todo_response(status: StatusCode, todo: Todo) -> Response {
+    (status, TypedHeader(todo_etag(todo.version)), Json(todo)).into_response()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::datasources::database::models::DbTodo;
-    use crate::datasources::database::MockDatabase;
+    use crate::datasources::database::models::{DbIdempotencyKey, DbPriority, DbTodo};
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
     use crate::server::domain::errors::ErrorResponse;
-    use crate::server::domain::todos::{NewTodo, Todo};
+    use crate::server::domain::todos::{NewTodo, Priority, Todo};
     use crate::server::handlers::todos_create::todos_create;
-    use crate::test_utils::{init_router, read_response_body, test_post};
-    use axum::http::StatusCode;
+    use crate::server::idempotency::request_hash;
+    use crate::test_utils::{
+        db_todo, init_router, read_response_body, test_post, test_with_headers,
+    };
+    use axum::http::{header, StatusCode};
     use axum::routing::post;
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
     use uuid::Uuid;
 
+    fn stored_key(text: &str, response: Option<&Todo>) -> DbIdempotencyKey {
+        DbIdempotencyKey {
+            owner: "user".to_string(),
+            key: "key".to_string(),
+            request_hash: request_hash(&json!({ "text": text })).unwrap(),
+            response_status: response.map(|_| 201),
+            response_body: response.map(|todo| serde_json::to_string(todo).unwrap()),
+            created_at: Utc::now(),
+            expires_at: Utc::now(),
+        }
+    }
+
+    async fn post_with_key(app: axum::Router, text: &str) -> axum::response::Response {
+        test_with_headers(
+            app,
+            "POST",
+            "/todos",
+            &[("Idempotency-Key", "key")],
+            Some(json!({ "text": text })),
+        )
+        .await
+    }
+
     #[tokio::test]
     async fn test_todos_create() {
-        let mut mock_db = MockDatabase::new();
-        mock_db.expect_insert().returning(|new_todo| {
-            Ok(DbTodo {
-                id: Uuid::new_v4(),
-                text: new_todo.text,
-                completed: false,
-            })
-        });
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert()
+            .withf(|owner, _| owner == "user")
+            .returning(|owner, new_todo| Ok(db_todo(Uuid::new_v4(), owner, &new_todo.text)));
         let app = init_router(mock_db, "/todos", post(todos_create)).await;
 
         let new_todo = NewTodo {
             text: "test".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
         };
         let response = test_post(app, "/todos", new_todo).await;
         assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::ETAG], "\"1\"");
 
         let todo: Todo = read_response_body(response).await;
         assert_eq!(todo.text, "test");
         assert!(!todo.completed);
+        assert_eq!(todo.version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_list_not_found() {
+        let list_id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert()
+            .withf(move |_, new_todo| new_todo.list_id == Some(list_id))
+            .returning(|_, new_todo| {
+                Err(DatabaseError::ListNotFound {
+                    id: new_todo.list_id.unwrap(),
+                })
+            });
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let new_todo = NewTodo {
+            text: "test".to_string(),
+            list_id: Some(list_id),
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
+        };
+        let response = test_post(app, "/todos", new_todo).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            format!("list not found with id: {}", list_id)
+        );
     }
 
     #[tokio::test]
     async fn test_todos_create_invalid_text_too_short() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/todos", post(todos_create)).await;
 
         let invalid_todo = NewTodo {
             text: "".to_string(),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
         };
         let response = test_post(app, "/todos", invalid_todo).await;
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
@@ -95,11 +245,15 @@ mod tests {
 
     #[tokio::test]
     async fn test_todos_create_invalid_text_too_long() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/todos", post(todos_create)).await;
 
         let invalid_todo = NewTodo {
             text: "a".repeat(201),
+            list_id: None,
+            tags: Vec::new(),
+            due_at: None,
+            priority: None,
         };
         let response = test_post(app, "/todos", invalid_todo).await;
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
@@ -110,4 +264,183 @@ mod tests {
             "text: length must be between 1 and 200"
         );
     }
+
+    #[tokio::test]
+    async fn test_todos_create_tags() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert()
+            .withf(|_, new_todo| new_todo.tags == ["work", "urgent"])
+            .returning(|owner, new_todo| {
+                Ok(DbTodo {
+                    tags: vec!["urgent".to_string(), "work".to_string()],
+                    ..db_todo(Uuid::new_v4(), owner, &new_todo.text)
+                })
+            });
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let new_todo = NewTodo {
+            text: "test".to_string(),
+            list_id: None,
+            tags: vec!["work".to_string(), "urgent".to_string()],
+            due_at: None,
+            priority: None,
+        };
+        let response = test_post(app, "/todos", new_todo).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.tags, ["urgent", "work"]);
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_due_at_priority() {
+        let due_at = "2025-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert()
+            .withf(move |_, new_todo| {
+                new_todo.due_at == Some(due_at) && new_todo.priority == DbPriority::High
+            })
+            .returning(|owner, new_todo| {
+                Ok(DbTodo {
+                    due_at: new_todo.due_at,
+                    priority: new_todo.priority,
+                    ..db_todo(Uuid::new_v4(), owner, &new_todo.text)
+                })
+            });
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let new_todo =
+            json!({ "text": "test", "due_at": "2025-03-01T12:00:00Z", "priority": "high" });
+        let response = test_post(app, "/todos", new_todo).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.due_at, Some(due_at));
+        assert_eq!(todo.priority, Priority::High);
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_invalid_tags() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let invalid_todo = NewTodo {
+            text: "test".to_string(),
+            list_id: None,
+            tags: (0..11).map(|i| format!("tag{}", i)).collect(),
+            due_at: None,
+            priority: None,
+        };
+        let response = test_post(app, "/todos", invalid_todo).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "tags: must have at most 10 tags");
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_idempotency_key() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_idempotency_key()
+            .withf(|owner, key| owner == "user" && key.key == "key")
+            .returning(|_, _| Ok(None));
+        mock_db
+            .expect_insert()
+            .times(1)
+            .returning(|owner, new_todo| Ok(db_todo(Uuid::new_v4(), owner, &new_todo.text)));
+        mock_db
+            .expect_complete_idempotency_key()
+            .withf(|owner, key, response| owner == "user" && key == "key" && response.status == 201)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let response = post_with_key(app, "test").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(!response.headers().contains_key("idempotent-replayed"));
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.text, "test");
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_idempotency_key_replayed() {
+        let todo: Todo = db_todo(Uuid::new_v4(), "user", "test").into();
+        let stored = stored_key("test", Some(&todo));
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_idempotency_key()
+            .returning(move |_, _| Ok(Some(stored.clone())));
+        mock_db.expect_insert().never();
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let response = post_with_key(app, "test").await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["idempotent-replayed"], "true");
+        assert_eq!(response.headers()[header::ETAG], "\"1\"");
+
+        let replayed: Todo = read_response_body(response).await;
+        assert_eq!(replayed.id, todo.id);
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_idempotency_key_other_body() {
+        let todo: Todo = db_todo(Uuid::new_v4(), "user", "test").into();
+        let stored = stored_key("test", Some(&todo));
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_idempotency_key()
+            .returning(move |_, _| Ok(Some(stored.clone())));
+        mock_db.expect_insert().never();
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let response = post_with_key(app, "other").await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "Idempotency-Key was already used with another request"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_idempotency_key_in_progress() {
+        let stored = stored_key("test", None);
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_idempotency_key()
+            .returning(move |_, _| Ok(Some(stored.clone())));
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let response = post_with_key(app, "test").await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_todos_create_idempotency_key_released_on_error() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_idempotency_key()
+            .returning(|_, _| Ok(None));
+        mock_db
+            .expect_insert()
+            .returning(|_, _| Err(DatabaseError::Internal(anyhow::anyhow!("connection lost"))));
+        mock_db
+            .expect_remove_idempotency_key()
+            .withf(|owner, key| owner == "user" && key == "key")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let app = init_router(mock_db, "/todos", post(todos_create)).await;
+
+        let response = post_with_key(app, "test").await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
 }
@@ -1,5 +1,11 @@
 use crate::{
-    server::{domain::errors::ErrorResponse, errors::AppError, openapi::TODO_TAG},
+    server::{
+        domain::errors::ErrorResponse,
+        errors::AppError,
+        extractors::{if_match::IfMatchVersion, require_role::RequireRole},
+        openapi::TODO_TAG,
+        roles::Editor,
+    },
     SharedState,
 };
 use axum::{
@@ -11,45 +17,60 @@ use uuid::Uuid;
 /// Delete Todo item by id
 ///
 /// Delete Todo item from in-memory storage by id. Returns either 200 success of 404 with TodoError if Todo is not found.
+/// With `If-Match` the item is only deleted if its `ETag` still matches, otherwise 412 is returned.
 #[utoipa::path(
     delete,
     path = "/{id}",
     tag = TODO_TAG,
     responses(
         (status = 200, description = "Todo deleted successfully"),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
         (status = 404, description = "Todo not found"),
+        (status = 412, description = "Todo was modified since the given ETag", body = ErrorResponse),
         (status = 500, description = "Internal error", body = ErrorResponse)
     ),
     params(
-        ("id" = String, Path, description = "Todo id")
+        ("id" = String, Path, description = "Todo id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the todo to delete"),
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
     )
 )]
 pub async fn todos_delete(
     Path(id): Path<String>,
     State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    IfMatchVersion(version): IfMatchVersion,
 ) -> Result<StatusCode, AppError> {
     let todo_id =
         Uuid::parse_str(&id) // validate id is UUID
             .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
 
-    state.db.remove(todo_id).await?;
+    state.db.remove(&user, todo_id, version).await?;
     Ok(StatusCode::OK)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::datasources::database::{DatabaseError, MockDatabase};
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
     use crate::server::domain::errors::ErrorResponse;
     use crate::server::handlers::todos_delete::todos_delete;
-    use crate::test_utils::{init_router, read_response_body, test_delete};
+    use crate::server::roles::Role;
+    use crate::test_utils::{
+        init_router, read_response_body, test_bearer_role, test_delete, test_with_headers,
+    };
     use axum::http::StatusCode;
     use axum::routing::delete;
     use uuid::Uuid;
 
     #[tokio::test]
     async fn test_todos_delete() {
-        let mut mock_db = MockDatabase::new();
-        mock_db.expect_remove().returning(|_| Ok(()));
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_remove().returning(|_, _, _| Ok(()));
         let app = init_router(mock_db, "/todos/{id}", delete(todos_delete)).await;
 
         let id = Uuid::new_v4().to_string();
@@ -57,12 +78,42 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_todos_delete_if_match() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_remove()
+            .withf(|_, _, version| *version == Some(2))
+            .returning(|_, _, _| Ok(()));
+        let app = init_router(mock_db, "/todos/{id}", delete(todos_delete)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let response = test_with_headers(app, "DELETE", &uri, &[("If-Match", "\"2\"")], None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_todos_delete_version_mismatch() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_remove().returning(|_, id, _| {
+            Err(DatabaseError::VersionMismatch {
+                id,
+                expected_version: 2,
+            })
+        });
+        let app = init_router(mock_db, "/todos/{id}", delete(todos_delete)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let response = test_with_headers(app, "DELETE", &uri, &[("If-Match", "\"2\"")], None).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
     #[tokio::test]
     async fn test_todos_delete_not_found() {
-        let mut mock_db = MockDatabase::new();
+        let mut mock_db = MockTodoRepository::new();
         mock_db
             .expect_remove()
-            .returning(|_| Err(DatabaseError::NotFound { id: Uuid::new_v4() }));
+            .returning(|_, _, _| Err(DatabaseError::NotFound { id: Uuid::new_v4() }));
         let app = init_router(mock_db, "/todos/{id}", delete(todos_delete)).await;
 
         let id = Uuid::new_v4().to_string();
@@ -70,9 +121,21 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_todos_delete_viewer() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos/{id}", delete(todos_delete)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let header = test_bearer_role("user", Role::Viewer);
+        let response =
+            test_with_headers(app, "DELETE", &uri, &[("Authorization", &header)], None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
     #[tokio::test]
     async fn test_todos_delete_invalid_id() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/todos/{id}", delete(todos_delete)).await;
 
         let response = test_delete(app, &format!("/todos/{}", "invalid")).await;
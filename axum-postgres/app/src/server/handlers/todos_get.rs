@@ -0,0 +1,186 @@
+use crate::{
+    server::{
+        domain::{errors::ErrorResponse, etag::todo_etag, todos::Todo},
+        errors::AppError,
+        extractors::require_role::RequireRole,
+        openapi::TODO_TAG,
+        roles::Viewer,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{
+    headers::{HeaderMapExt, IfNoneMatch},
+    TypedHeader,
+};
+use uuid::Uuid;
+
+/// Get Todo item by id
+///
+/// Get Todo item by given id. Returns 304 without a body if `If-None-Match` has the current `ETag` of the item.
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = TODO_TAG,
+    responses(
+        (status = 200, description = "Todo found", body = Todo,
+            headers(("ETag" = String, description = "Version of the todo"))),
+        (status = 304, description = "Todo not modified since the given ETag"),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "Todo not found"),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "Todo id"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched todo"),
+    ),
+    security(
+        ("basic_auth" = ["viewer"]),
+        ("bearer_auth" = ["viewer"]),
+        ("api_key" = ["viewer"])
+    )
+)]
+pub async fn todos_get(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Viewer>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let todo_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+
+    let todo: Todo = state.db.get(&user, todo_id).await?.into();
+    let etag = todo_etag(todo.version);
+    // an invalid If-None-Match header is ignored
+    let not_modified = headers
+        .typed_get::<IfNoneMatch>()
+        .is_some_and(|if_none_match| !if_none_match.precondition_passes(&etag));
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, TypedHeader(etag)).into_response());
+    }
+    Ok((TypedHeader(etag), Json(todo)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbTodo;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::domain::todos::Todo;
+    use crate::server::handlers::todos_get::todos_get;
+    use crate::test_utils::{
+        db_todo, init_router, read_response_body, test_authenticated, test_bearer, test_get,
+        test_with_headers,
+    };
+    use axum::http::{header, StatusCode};
+    use axum::routing::get;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_todos_get() {
+        let id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get()
+            .withf(move |owner, get_id| owner == "user" && *get_id == id)
+            .returning(|_, id| {
+                Ok(DbTodo {
+                    version: 2,
+                    ..db_todo(id, "user", "test")
+                })
+            });
+        let app = init_router(mock_db, "/todos/{id}", get(todos_get)).await;
+
+        let response = test_get(app, &format!("/todos/{}", id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], "\"2\"");
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.id, id.to_string());
+        assert_eq!(todo.text, "test");
+    }
+
+    #[tokio::test]
+    async fn test_todos_get_if_none_match() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| {
+            Ok(DbTodo {
+                version: 2,
+                ..db_todo(id, "user", "test")
+            })
+        });
+        let app = init_router(mock_db, "/todos/{id}", get(todos_get)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let not_modified = test_with_headers(
+            app.clone(),
+            "GET",
+            &uri,
+            &[("If-None-Match", "\"2\"")],
+            None,
+        )
+        .await;
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+
+        let modified =
+            test_with_headers(app, "GET", &uri, &[("If-None-Match", "\"1\"")], None).await;
+        assert_eq!(modified.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_todos_get_of_other_user() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get()
+            .withf(|owner, _| owner == "admin")
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/todos/{id}", get(todos_get)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let response = test_authenticated(app, &uri, "GET", &test_bearer("admin")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_todos_get_unauthorized() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos/{id}", get(todos_get)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let response = test_authenticated(app, &uri, "GET", "Bearer invalid").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_todos_get_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get()
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/todos/{id}", get(todos_get)).await;
+
+        let response = test_get(app, &format!("/todos/{}", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_todos_get_invalid_id() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos/{id}", get(todos_get)).await;
+
+        let response = test_get(app, "/todos/invalid").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "id is not valid uuid: invalid");
+    }
+}
@@ -1,49 +1,197 @@
 use crate::{
+    datasources::database::models::{DbTodo, DbTodosAfter, DbTodosQuery, DbTodosSort},
     server::{
         domain::{
+            cursor::Cursor,
             errors::ErrorResponse,
-            todos::{Todo, TodosResponse},
+            etag::todos_etag,
+            todos::{Todo, TodosQuery, TodosResponse, DEFAULT_PAGE_LIMIT},
         },
         errors::AppError,
+        extractors::{request_query::ValidatedQuery, require_role::RequireRole},
         openapi::TODO_TAG,
+        roles::Viewer,
     },
     SharedState,
 };
-use axum::{extract::State, Json};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{
+    headers::{HeaderMapExt, IfNoneMatch},
+    TypedHeader,
+};
+use uuid::Uuid;
 
-/// List all Todo items
+/// List Todo items
 ///
-/// List all Todo items from in-memory storage.
+/// List Todo items one page at a time, optionally filtered by completion, full-text search, tags or time ranges and sorted by text, creation or update time. Pass the returned `next_cursor` as `cursor` to get the next page.
+/// Returns 304 without a body if `If-None-Match` has the `ETag` of the same page.
 #[utoipa::path(
     get,
     path = "/",
     tag = TODO_TAG,
+    params(
+        TodosQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched page"),
+    ),
     responses(
-        (status = 200, description = "List all todos successfully", body = TodosResponse ),
+        (status = 200, description = "List todos successfully", body = TodosResponse,
+            headers(("ETag" = String, description = "Version of the page"))),
+        (status = 304, description = "Page not modified since the given ETag"),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "limit: must be between 1 and 100".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
         (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["viewer"]),
+        ("bearer_auth" = ["viewer"]),
+        ("api_key" = ["viewer"])
     )
 )]
-pub async fn todos_list(State(state): State<SharedState>) -> Result<Json<TodosResponse>, AppError> {
-    let db_todos = state.db.get_values().await?;
+pub async fn todos_list(
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Viewer>,
+    headers: HeaderMap,
+    ValidatedQuery(query): ValidatedQuery<TodosQuery>,
+) -> Result<Response, AppError> {
+    list_todos(&state, &user, None, &headers, query).await
+}
+
+/// Page of the todos of the owner, or of one of their lists, shared with the endpoints listing
+/// the todos of a list and of a user.
+pub(crate) async fn list_todos(
+    state: &SharedState,
+    owner: &str,
+    list_id: Option<Uuid>,
+    headers: &HeaderMap,
+    query: TodosQuery,
+) -> Result<Response, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+    let sort: DbTodosSort = query.sort.map(Into::into).unwrap_or_default();
+    let after = match &query.cursor {
+        Some(cursor) => Some(
+            decode_cursor(cursor, sort)
+                .ok_or_else(|| AppError::BadRequest(format!("cursor is not valid: {}", cursor)))?,
+        ),
+        None => None,
+    };
+
+    // fetch one extra row to know if there is a next page
+    let mut db_todos = state
+        .db
+        .get_values(
+            owner,
+            DbTodosQuery {
+                limit: limit as i64 + 1,
+                after,
+                completed: query.completed,
+                created_at: query.created_at(),
+                updated_at: query.updated_at(),
+                completed_at: query.completed_at(),
+                due_at: query.due_at(),
+                search: query.q,
+                sort,
+                list_id,
+                tags: unique_tags(query.tag),
+                tags_match: query.tag_match.map(Into::into).unwrap_or_default(),
+            },
+        )
+        .await?;
+    let next_cursor = if db_todos.len() > limit {
+        db_todos.truncate(limit);
+        db_todos.last().map(|last| encode_cursor(last, sort))
+    } else {
+        None
+    };
+
     let todos: Vec<Todo> = db_todos.into_iter().map(|db_todo| db_todo.into()).collect();
-    Ok(Json(TodosResponse { todos }))
+    let response = TodosResponse { todos, next_cursor };
+    let etag = todos_etag(&response);
+    // an invalid If-None-Match header is ignored
+    let not_modified = headers
+        .typed_get::<IfNoneMatch>()
+        .is_some_and(|if_none_match| !if_none_match.precondition_passes(&etag));
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, TypedHeader(etag)).into_response());
+    }
+    Ok((TypedHeader(etag), Json(response)).into_response())
+}
+
+/// Tags of the filter without duplicates, as required to match all of them.
+fn unique_tags(mut tags: Vec<String>) -> Vec<String> {
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn encode_cursor(last: &DbTodo, sort: DbTodosSort) -> String {
+    let cursor = Cursor {
+        id: last.id,
+        text: matches!(sort, DbTodosSort::Text | DbTodosSort::TextDesc).then(|| last.text.clone()),
+        created_at: matches!(sort, DbTodosSort::CreatedAt | DbTodosSort::CreatedAtDesc)
+            .then_some(last.created_at),
+        updated_at: matches!(sort, DbTodosSort::UpdatedAt | DbTodosSort::UpdatedAtDesc)
+            .then_some(last.updated_at),
+        due_at: matches!(sort, DbTodosSort::DueAt | DbTodosSort::DueAtDesc)
+            .then(|| last.due_at_key()),
+        priority: matches!(sort, DbTodosSort::Priority | DbTodosSort::PriorityDesc)
+            .then(|| last.priority.into()),
+    };
+    cursor.encode()
+}
+
+/// Cursor is valid only if it has the sort key of the requested sort.
+fn decode_cursor(cursor: &str, sort: DbTodosSort) -> Option<DbTodosAfter> {
+    let cursor = Cursor::decode(cursor)?;
+    let has_sort_key = match sort {
+        DbTodosSort::Id => true,
+        DbTodosSort::Text | DbTodosSort::TextDesc => cursor.text.is_some(),
+        DbTodosSort::CreatedAt | DbTodosSort::CreatedAtDesc => cursor.created_at.is_some(),
+        DbTodosSort::UpdatedAt | DbTodosSort::UpdatedAtDesc => cursor.updated_at.is_some(),
+        DbTodosSort::DueAt | DbTodosSort::DueAtDesc => cursor.due_at.is_some(),
+        DbTodosSort::Priority | DbTodosSort::PriorityDesc => cursor.priority.is_some(),
+    };
+    has_sort_key.then_some(DbTodosAfter {
+        id: cursor.id,
+        text: cursor.text,
+        created_at: cursor.created_at,
+        updated_at: cursor.updated_at,
+        due_at: cursor.due_at,
+        priority: cursor.priority.map(Into::into),
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::datasources::database::models::DbTodo;
-    use crate::datasources::database::MockDatabase;
-    use crate::server::domain::todos::TodosResponse;
+    use crate::datasources::database::models::{
+        DbPriority, DbTagsMatch, DbTodo, DbTodosSort, NO_DUE_AT,
+    };
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::cursor::Cursor;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::domain::todos::{Priority, TodosResponse};
     use crate::server::handlers::todos_list::todos_list;
-    use crate::test_utils::{init_router, read_response_body, test_get};
-    use axum::http::StatusCode;
+    use crate::test_utils::{
+        db_todo, init_router, read_response_body, test_get, test_with_headers,
+    };
+    use axum::http::{header, StatusCode};
     use axum::routing::get;
+    use chrono::{DateTime, Utc};
     use uuid::Uuid;
 
     #[tokio::test]
     async fn test_todos_list_empty() {
-        let mut mock_db = MockDatabase::new();
-        mock_db.expect_get_values().returning(|| Ok(vec![]));
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(|_, query| query.limit == 51 && query.after.is_none())
+            .returning(|_, _| Ok(vec![]));
         let app = init_router(mock_db, "/todos", get(todos_list)).await;
 
         let response = test_get(app, "/todos").await;
@@ -51,18 +199,20 @@ mod tests {
 
         let response_body: TodosResponse = read_response_body(response).await;
         assert!(response_body.todos.is_empty());
+        assert!(response_body.next_cursor.is_none());
     }
 
     #[tokio::test]
     async fn test_todos_list_results() {
         let id = Uuid::new_v4();
+        let created_at = Utc::now();
 
-        let mut mock_db = MockDatabase::new();
-        mock_db.expect_get_values().returning(move || {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get_values().returning(move |_, _| {
             Ok(vec![DbTodo {
-                id: id.clone(),
-                text: "test".to_string(),
-                completed: false,
+                created_at,
+                updated_at: created_at,
+                ..db_todo(id, "user", "test")
             }])
         });
         let app = init_router(mock_db, "/todos", get(todos_list)).await;
@@ -76,5 +226,315 @@ mod tests {
         assert_eq!(todo.id, id.to_string());
         assert_eq!(todo.text, "test");
         assert!(!todo.completed);
+        assert_eq!(todo.created_at, created_at);
+        assert_eq!(todo.updated_at, created_at);
+        assert_eq!(todo.completed_at, None);
+        assert!(response_body.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_if_none_match() {
+        let id = Uuid::new_v4();
+        let todo = move |version| DbTodo {
+            version,
+            ..db_todo(id, "user", "test")
+        };
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .times(2)
+            .returning(move |_, _| Ok(vec![todo(1)]));
+        mock_db
+            .expect_get_values()
+            .returning(move |_, _| Ok(vec![todo(2)]));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app.clone(), "/todos").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let headers = [("If-None-Match", etag.as_str())];
+        let not_modified = test_with_headers(app.clone(), "GET", "/todos", &headers, None).await;
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(not_modified.headers()[header::ETAG], etag.as_str());
+
+        // the todo was updated
+        let modified = test_with_headers(app, "GET", "/todos", &headers, None).await;
+        assert_eq!(modified.status(), StatusCode::OK);
+        assert_ne!(modified.headers()[header::ETAG], etag.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_next_page() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let rows = ids.clone();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(|_, query| query.limit == 3)
+            .returning(move |_, _| {
+                Ok(rows.iter().map(|id| db_todo(*id, "user", "test")).collect())
+            });
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?limit=2").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TodosResponse = read_response_body(response).await;
+        assert_eq!(response_body.todos.len(), 2);
+        let next_cursor = Cursor::decode(&response_body.next_cursor.unwrap()).unwrap();
+        assert_eq!(next_cursor.id, ids[1]);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_with_cursor() {
+        let after = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(move |_, query| query.after.as_ref().is_some_and(|a| a.id == after))
+            .returning(|_, _| Ok(vec![]));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let cursor = Cursor {
+            id: after,
+            text: None,
+            created_at: None,
+            updated_at: None,
+            due_at: None,
+            priority: None,
+        };
+        let uri = format!("/todos?cursor={}", cursor.encode());
+        let response = test_get(app, &uri).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_filters() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(|_, query| {
+                query.completed == Some(true)
+                    && query.search.as_deref() == Some("buy milk")
+                    && query.sort == DbTodosSort::TextDesc
+            })
+            .returning(|_, _| Ok(vec![]));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?completed=true&q=buy%20milk&sort=-text").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_due_filters() {
+        let due_after = "2025-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let due_before = "2025-04-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(move |_, query| {
+                query.due_at.after == Some(due_after)
+                    && query.due_at.before == Some(due_before)
+                    && query.sort == DbTodosSort::DueAt
+                    && query.limit == 2
+            })
+            .returning(|owner, _| {
+                Ok((0..2)
+                    .map(|_| DbTodo {
+                        priority: DbPriority::Low,
+                        ..db_todo(Uuid::new_v4(), owner, "test")
+                    })
+                    .collect())
+            });
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(
+            app,
+            "/todos?due_after=2025-03-01T00:00:00Z&due_before=2025-04-01T00:00:00Z&sort=due_at&limit=1",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // items without a due date continue after the latest due date
+        let response_body: TodosResponse = read_response_body(response).await;
+        let cursor = Cursor::decode(&response_body.next_cursor.unwrap()).unwrap();
+        assert_eq!(cursor.due_at, Some(NO_DUE_AT));
+        assert_eq!(response_body.todos[0].priority, Priority::Low);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_tag_filters() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(|_, query| {
+                query.tags == ["home", "urgent"] && query.tags_match == DbTagsMatch::Any
+            })
+            .returning(|_, _| Ok(vec![]));
+        mock_db
+            .expect_get_values()
+            .withf(|_, query| query.tags == ["home"] && query.tags_match == DbTagsMatch::All)
+            .returning(|_, _| Ok(vec![]));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(
+            app.clone(),
+            "/todos?tag=urgent&tag=home&tag=urgent&tag_match=any",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = test_get(app.clone(), "/todos?tag=home").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = test_get(app, "/todos?tag=").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "tag: tag length must be between 1 and 50"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_time_filters() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(|_, query| {
+                let time = |s: &str| Some(s.parse::<DateTime<Utc>>().unwrap());
+                query.created_at.after == time("2025-01-01T00:00:00Z")
+                    && query.created_at.before.is_none()
+                    && query.updated_at.is_unbounded()
+                    && query.completed_at.before == time("2025-02-01T12:00:00+01:00")
+                    && query.sort == DbTodosSort::UpdatedAtDesc
+            })
+            .returning(|_, _| Ok(vec![]));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(
+            app,
+            "/todos?created_after=2025-01-01T00:00:00Z&completed_before=2025-02-01T12:00:00%2B01:00&sort=-updated_at",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_invalid_time() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?updated_after=yesterday").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_updated_at_next_page() {
+        let rows: Vec<DbTodo> = (0..3)
+            .map(|_| db_todo(Uuid::new_v4(), "user", "test"))
+            .collect();
+        let second = rows[1].clone();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .returning(move |_, _| Ok(rows.clone()));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?limit=2&sort=updated_at").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TodosResponse = read_response_body(response).await;
+        let next_cursor = Cursor::decode(&response_body.next_cursor.unwrap()).unwrap();
+        assert_eq!(next_cursor.id, second.id);
+        assert_eq!(next_cursor.updated_at, Some(second.updated_at));
+        assert_eq!(next_cursor.created_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_sorted_next_page() {
+        let rows: Vec<DbTodo> = ["a", "b", "c"]
+            .iter()
+            .map(|text| db_todo(Uuid::new_v4(), "user", text))
+            .collect();
+        let second = rows[1].clone();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .returning(move |_, _| Ok(rows.clone()));
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?limit=2&sort=text").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TodosResponse = read_response_body(response).await;
+        let next_cursor = Cursor::decode(&response_body.next_cursor.unwrap()).unwrap();
+        assert_eq!(next_cursor.id, second.id);
+        assert_eq!(next_cursor.text, Some(second.text));
+        assert_eq!(next_cursor.created_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_cursor_without_sort_key() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let cursor = Cursor {
+            id: Uuid::new_v4(),
+            text: None,
+            created_at: None,
+            updated_at: None,
+            due_at: None,
+            priority: None,
+        }
+        .encode();
+        let response = test_get(app, &format!("/todos?sort=created_at&cursor={}", cursor)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_invalid_sort() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?sort=unknown").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "failed to read query");
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_invalid_cursor() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?cursor=invalid").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "cursor is not valid: invalid");
+    }
+
+    #[tokio::test]
+    async fn test_todos_list_invalid_limit() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos", get(todos_list)).await;
+
+        let response = test_get(app, "/todos?limit=0").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "limit: must be between 1 and 100");
     }
 }
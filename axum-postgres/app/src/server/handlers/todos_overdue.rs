@@ -0,0 +1,129 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            todos::{TodosQuery, TodosResponse, TodosSort},
+        },
+        errors::AppError,
+        extractors::{request_query::ValidatedQuery, require_role::RequireRole},
+        handlers::todos_list::list_todos,
+        openapi::TODO_TAG,
+        roles::Viewer,
+    },
+    SharedState,
+};
+use axum::{extract::State, http::HeaderMap, response::Response};
+use chrono::Utc;
+
+/// List overdue Todo items
+///
+/// List the Todo items that are not completed and were due before now, the earliest due first unless sorted otherwise. Takes the same query parameters as listing all todos, `completed` is ignored and `due_before` can only move the end of the range earlier.
+#[utoipa::path(
+    get,
+    path = "/overdue",
+    tag = TODO_TAG,
+    params(
+        TodosQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched page"),
+    ),
+    responses(
+        (status = 200, description = "List overdue todos successfully", body = TodosResponse,
+            headers(("ETag" = String, description = "Version of the page"))),
+        (status = 304, description = "Page not modified since the given ETag"),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "limit: must be between 1 and 100".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    security(
+        ("basic_auth" = ["viewer"]),
+        ("bearer_auth" = ["viewer"]),
+        ("api_key" = ["viewer"])
+    )
+)]
+pub async fn todos_overdue(
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Viewer>,
+    headers: HeaderMap,
+    ValidatedQuery(query): ValidatedQuery<TodosQuery>,
+) -> Result<Response, AppError> {
+    let now = Utc::now();
+    let query = TodosQuery {
+        completed: Some(false),
+        due_before: Some(
+            query
+                .due_before
+                .map_or(now, |due_before| due_before.min(now)),
+        ),
+        sort: Some(query.sort.unwrap_or(TodosSort::DueAt)),
+        ..query
+    };
+    list_todos(&state, &user, None, &headers, query).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::{DbPriority, DbTodo, DbTodosSort};
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::domain::todos::TodosResponse;
+    use crate::server::handlers::todos_overdue::todos_overdue;
+    use crate::test_utils::{db_todo, init_router, read_response_body, test_get};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chrono::{DateTime, Duration, Utc};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_todos_overdue() {
+        let due_at = Utc::now() - Duration::hours(1);
+        let start = Utc::now();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(move |owner, query| {
+                owner == "user"
+                    && query.completed == Some(false)
+                    && query.due_at.before.is_some_and(|before| before >= start)
+                    && query.due_at.after.is_none()
+                    && query.sort == DbTodosSort::DueAt
+            })
+            .returning(move |owner, _| {
+                Ok(vec![DbTodo {
+                    due_at: Some(due_at),
+                    priority: DbPriority::High,
+                    ..db_todo(Uuid::new_v4(), owner, "Pay rent")
+                }])
+            });
+        let app = init_router(mock_db, "/todos/overdue", get(todos_overdue)).await;
+
+        let response = test_get(app, "/todos/overdue?completed=true").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response_body: TodosResponse = read_response_body(response).await;
+        assert_eq!(response_body.todos.len(), 1);
+        assert_eq!(response_body.todos[0].due_at, Some(due_at));
+    }
+
+    #[tokio::test]
+    async fn test_todos_overdue_due_before() {
+        let due_before = "2025-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get_values()
+            .withf(move |_, query| {
+                query.due_at.before == Some(due_before) && query.sort == DbTodosSort::PriorityDesc
+            })
+            .returning(|_, _| Ok(vec![]));
+        let app = init_router(mock_db, "/todos/overdue", get(todos_overdue)).await;
+
+        let response = test_get(
+            app,
+            "/todos/overdue?due_before=2025-01-01T00:00:00Z&sort=-priority",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
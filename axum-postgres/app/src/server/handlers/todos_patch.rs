@@ -0,0 +1,385 @@
+use crate::{
+    datasources::database::DatabaseError,
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            etag::todo_etag,
+            todos::{ReplaceTodo, Todo, UpdateTodo},
+        },
+        errors::AppError,
+        extractors::{if_match::IfMatchVersion, require_role::RequireRole},
+        openapi::TODO_TAG,
+        roles::Editor,
+    },
+    SharedState,
+};
+use anyhow::Context;
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header::CONTENT_TYPE, HeaderMap},
+    Json,
+};
+use axum_extra::{headers::ETag, TypedHeader};
+use json_patch::Patch;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+const MERGE_PATCH: &str = "application/merge-patch+json";
+const JSON_PATCH: &str = "application/json-patch+json";
+
+/// Fields of the Todo representation that a patch may change.
+const PATCHABLE_FIELDS: [&str; 6] = ["text", "completed", "list_id", "tags", "due_at", "priority"];
+
+enum TodoPatch {
+    /// RFC 7396 JSON Merge Patch
+    Merge(Value),
+    /// RFC 6902 JSON Patch
+    Json(Patch),
+}
+
+impl TodoPatch {
+    fn parse(headers: &HeaderMap, body: &Bytes) -> Result<Self, AppError> {
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        let invalid_json = |_| AppError::BadRequest("failed to read json".to_string());
+        match content_type.as_str() {
+            MERGE_PATCH => Ok(TodoPatch::Merge(
+                serde_json::from_slice(body).map_err(invalid_json)?,
+            )),
+            JSON_PATCH => Ok(TodoPatch::Json(
+                serde_json::from_slice(body).map_err(invalid_json)?,
+            )),
+            _ => Err(AppError::UnsupportedMediaType(format!(
+                "content type must be {} or {}",
+                MERGE_PATCH, JSON_PATCH
+            ))),
+        }
+    }
+
+    fn apply(&self, todo: &mut Value) -> Result<(), AppError> {
+        match self {
+            TodoPatch::Merge(patch) => json_patch::merge(todo, patch),
+            TodoPatch::Json(patch) => json_patch::patch(todo, patch).map_err(|e| {
+                AppError::UnprocessableEntity(format!("patch cannot be applied: {}", e))
+            })?,
+        }
+        Ok(())
+    }
+}
+
+/// Patch Todo item by id
+///
+/// Change the Todo item with given id with a JSON Merge Patch (`application/merge-patch+json`) or a JSON Patch (`application/json-patch+json`).
+/// The patch applies to the Todo as returned by the API but may only change `text`, `completed`, `list_id`, `tags`, `due_at` and `priority`.
+/// Fails with 412 if `If-Match` does not have the current `ETag` of the item or if it is changed while the patch is applied.
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    tag = TODO_TAG,
+    request_body(content(
+        (UpdateTodo = "application/merge-patch+json"),
+        (Patch = "application/json-patch+json"),
+    )),
+    responses(
+        (status = 200, description = "Todo patched successfully", body = Todo,
+            headers(("ETag" = String, description = "Version of the patched todo"))),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "text: length must be between 1 and 200".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "Todo not found"),
+        (status = 412, description = "Todo was modified since the given ETag", body = ErrorResponse),
+        (status = 415, description = "Unsupported patch format", body = ErrorResponse),
+        (status = 422, description = "Patch cannot be applied", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "only text, completed, list_id, tags, due_at and priority can be changed".to_string() })),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "Todo id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the todo to patch"),
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
+    )
+)]
+pub async fn todos_patch(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    IfMatchVersion(version): IfMatchVersion,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(TypedHeader<ETag>, Json<Todo>), AppError> {
+    let todo_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+    let patch = TodoPatch::parse(&headers, &body)?;
+
+    let current = state.db.get(&user, todo_id).await?;
+    if let Some(expected_version) = version.filter(|version| *version != current.version) {
+        return Err(DatabaseError::VersionMismatch {
+            id: todo_id,
+            expected_version,
+        }
+        .into());
+    }
+    let current_version = current.version;
+    let original = serde_json::to_value(Todo::from(current)).context("failed to serialize todo")?;
+    let mut patched = original.clone();
+    patch.apply(&mut patched)?;
+    if read_only_fields(&original) != read_only_fields(&patched) {
+        return Err(AppError::UnprocessableEntity(
+            "only text, completed, list_id, tags, due_at and priority can be changed".to_string(),
+        ));
+    }
+    let replace_todo: ReplaceTodo = serde_json::from_value(patched)
+        .map_err(|e| AppError::UnprocessableEntity(format!("patched todo is not valid: {}", e)))?;
+    replace_todo.validate()?;
+
+    // fails instead of overwriting changes made since the todo was read
+    let patched_todo = state
+        .db
+        .update(&user, todo_id, replace_todo.into(), Some(current_version))
+        .await?;
+    let todo: Todo = patched_todo.into();
+    Ok((TypedHeader(todo_etag(todo.version)), Json(todo)))
+}
+
+fn read_only_fields(todo: &Value) -> Option<Map<String, Value>> {
+    let fields = todo.as_object()?;
+    Some(
+        fields
+            .iter()
+            .filter(|(name, _)| !PATCHABLE_FIELDS.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbTodo;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::domain::todos::Todo;
+    use crate::server::handlers::todos_patch::todos_patch;
+    use crate::test_utils::{db_todo, init_router, read_response_body, test_bearer};
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::response::Response;
+    use axum::routing::patch;
+    use axum::Router;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    fn stored_todo(id: Uuid) -> DbTodo {
+        DbTodo {
+            version: 3,
+            ..db_todo(id, "user", "test")
+        }
+    }
+
+    /// Expects the todo to be read and updated from version 3 to 4.
+    fn mock_patch() -> MockTodoRepository {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+        mock_db
+            .expect_update()
+            .withf(|_, _, _, version| *version == Some(3))
+            .returning(|_, id, update_todo, _| {
+                Ok(DbTodo {
+                    text: update_todo.text.unwrap(),
+                    completed: update_todo.completed.unwrap(),
+                    version: 4,
+                    ..stored_todo(id)
+                })
+            });
+        mock_db
+    }
+
+    async fn send_patch(
+        mock_db: MockTodoRepository,
+        content_type: &str,
+        if_match: Option<&str>,
+        body: Value,
+    ) -> Response {
+        let app: Router = init_router(mock_db, "/todos/{id}", patch(todos_patch)).await;
+        let mut request = Request::builder()
+            .method("PATCH")
+            .uri(format!("/todos/{}", Uuid::new_v4()))
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::AUTHORIZATION, test_bearer("user"));
+        if let Some(if_match) = if_match {
+            request = request.header(header::IF_MATCH, if_match);
+        }
+        app.oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_merge() {
+        let body = json!({ "completed": true });
+        let response = send_patch(mock_patch(), "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], "\"4\"");
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.text, "test");
+        assert!(todo.completed);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_list() {
+        let list_id = Uuid::new_v4();
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+        mock_db
+            .expect_update()
+            .withf(move |_, _, update_todo, _| update_todo.list_id == Some(Some(list_id)))
+            .returning(|_, id, update_todo, _| {
+                Ok(DbTodo {
+                    list_id: update_todo.list_id.unwrap(),
+                    version: 4,
+                    ..stored_todo(id)
+                })
+            });
+
+        let body = json!({ "list_id": list_id });
+        let response = send_patch(mock_db, "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.list_id, Some(list_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_json_patch() {
+        let body = json!([
+            { "op": "test", "path": "/version", "value": 3 },
+            { "op": "replace", "path": "/text", "value": "patched" }
+        ]);
+        let response = send_patch(
+            mock_patch(),
+            "application/json-patch+json",
+            Some("\"3\""),
+            body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.text, "patched");
+        assert!(!todo.completed);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_failed_test_operation() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+
+        let body = json!([{ "op": "test", "path": "/text", "value": "other" }]);
+        let response = send_patch(mock_db, "application/json-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_read_only_field() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+
+        let body = json!({ "id": Uuid::new_v4() });
+        let response = send_patch(mock_db, "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "only text, completed, list_id, tags, due_at and priority can be changed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_removed_field() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+
+        let body = json!({ "text": null });
+        let response = send_patch(mock_db, "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_invalid_text() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+
+        let body = json!({ "text": "" });
+        let response = send_patch(mock_db, "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "text: length must be between 1 and 200"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_if_match_stale() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+
+        let body = json!({ "completed": true });
+        let response =
+            send_patch(mock_db, "application/merge-patch+json", Some("\"2\""), body).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_concurrent_update() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_get().returning(|_, id| Ok(stored_todo(id)));
+        mock_db.expect_update().returning(|_, id, _, version| {
+            Err(DatabaseError::VersionMismatch {
+                id,
+                expected_version: version.unwrap(),
+            })
+        });
+
+        let body = json!({ "completed": true });
+        let response = send_patch(mock_db, "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_unsupported_media_type() {
+        let mock_db = MockTodoRepository::new();
+
+        let body = json!({ "completed": true });
+        let response = send_patch(mock_db, "application/json", None, body).await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_todos_patch_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get()
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+
+        let body = json!({ "completed": true });
+        let response = send_patch(mock_db, "application/merge-patch+json", None, body).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
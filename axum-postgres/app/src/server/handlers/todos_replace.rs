@@ -0,0 +1,161 @@
+use crate::{
+    server::{
+        domain::{
+            errors::ErrorResponse,
+            etag::todo_etag,
+            todos::{ReplaceTodo, Todo},
+        },
+        errors::AppError,
+        extractors::{
+            if_match::IfMatchVersion, request_json::ValidatedJson, require_role::RequireRole,
+        },
+        openapi::TODO_TAG,
+        roles::Editor,
+    },
+    SharedState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use axum_extra::{headers::ETag, TypedHeader};
+use uuid::Uuid;
+
+/// Replace Todo item by id
+///
+/// Replace text and completion of the Todo item with given id, both are required. Returns 404 if Todo is not found.
+/// With `If-Match` the item is only replaced if its `ETag` still matches, otherwise 412 is returned.
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = TODO_TAG,
+    request_body = ReplaceTodo,
+    responses(
+        (status = 200, description = "Todo replaced successfully", body = Todo,
+            headers(("ETag" = String, description = "Version of the replaced todo"))),
+        (status = 400, description = "Bad request", body = ErrorResponse,
+            example = json!(ErrorResponse { error: "text: length must be between 1 and 200".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
+        (status = 404, description = "Todo not found"),
+        (status = 412, description = "Todo was modified since the given ETag", body = ErrorResponse),
+        (status = 500, description = "Internal error", body = ErrorResponse)
+    ),
+    params(
+        ("id" = String, Path, description = "Todo id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the todo to replace"),
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
+    )
+)]
+pub async fn todos_replace(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    IfMatchVersion(version): IfMatchVersion,
+    ValidatedJson(input): ValidatedJson<ReplaceTodo>,
+) -> Result<(TypedHeader<ETag>, Json<Todo>), AppError> {
+    let todo_id =
+        Uuid::parse_str(&id) // validate id is UUID
+            .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
+
+    let replaced_todo = state
+        .db
+        .update(&user, todo_id, input.into(), version)
+        .await?;
+    let todo: Todo = replaced_todo.into();
+    Ok((TypedHeader(todo_etag(todo.version)), Json(todo)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::models::DbTodo;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::domain::todos::Todo;
+    use crate::server::handlers::todos_replace::todos_replace;
+    use crate::test_utils::{db_todo, init_router, read_response_body, test_with_headers};
+    use axum::http::{header, StatusCode};
+    use axum::routing::put;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_todos_replace() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_update()
+            .withf(|_, _, update_todo, version| {
+                update_todo.text.as_deref() == Some("replaced")
+                    && update_todo.completed == Some(false)
+                    && *version == Some(1)
+            })
+            .returning(|_, id, update_todo, _| {
+                Ok(DbTodo {
+                    completed: update_todo.completed.unwrap(),
+                    version: 2,
+                    ..db_todo(id, "user", &update_todo.text.unwrap())
+                })
+            });
+        let app = init_router(mock_db, "/todos/{id}", put(todos_replace)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let body = json!({ "text": "replaced", "completed": false });
+        let response =
+            test_with_headers(app, "PUT", &uri, &[("If-Match", "\"1\"")], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], "\"2\"");
+
+        let todo: Todo = read_response_body(response).await;
+        assert_eq!(todo.text, "replaced");
+        assert!(!todo.completed);
+    }
+
+    #[tokio::test]
+    async fn test_todos_replace_missing_field() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos/{id}", put(todos_replace)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let body = json!({ "text": "replaced" });
+        let response = test_with_headers(app, "PUT", &uri, &[], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "failed to read json");
+    }
+
+    #[tokio::test]
+    async fn test_todos_replace_invalid_text() {
+        let mock_db = MockTodoRepository::new();
+        let app = init_router(mock_db, "/todos/{id}", put(todos_replace)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let body = json!({ "text": "", "completed": true });
+        let response = test_with_headers(app, "PUT", &uri, &[], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(
+            response_body.error,
+            "text: length must be between 1 and 200"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todos_replace_not_found() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_update()
+            .returning(|_, id, _, _| Err(DatabaseError::NotFound { id }));
+        let app = init_router(mock_db, "/todos/{id}", put(todos_replace)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let body = json!({ "text": "replaced", "completed": true });
+        let response = test_with_headers(app, "PUT", &uri, &[], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -2,78 +2,131 @@ use crate::{
     server::{
         domain::{
             errors::ErrorResponse,
+            etag::todo_etag,
             todos::{Todo, UpdateTodo},
         },
         errors::AppError,
-        extractors::request_json::ValidatedJson,
+        extractors::{
+            if_match::IfMatchVersion, request_json::ValidatedJson, require_role::RequireRole,
+        },
         openapi::TODO_TAG,
+        roles::Editor,
     },
     SharedState,
 };
 use axum::{
     extract::{Path, State},
+    http::{HeaderName, HeaderValue},
+    response::IntoResponse,
     Json,
 };
+use axum_extra::{headers::ETag, TypedHeader};
 use uuid::Uuid;
 
+/// RFC 9745 header with the time this endpoint was deprecated, 2025-02-01.
+const DEPRECATION: (HeaderName, HeaderValue) = (
+    HeaderName::from_static("deprecation"),
+    HeaderValue::from_static("@1738368000"),
+);
+
 /// Update Todo item by id
 ///
+/// Deprecated, use PATCH or PUT on the same path instead. Responses have a `Deprecation` header.
+///
 /// Update Todo item text or mark done by given id. Return only status 200 on success or 404 if Todo is not found.
+/// With `If-Match` the item is only updated if its `ETag` still matches, otherwise 412 is returned.
 #[utoipa::path(
     post,
     path = "/{id}",
     tag = TODO_TAG,
     request_body = UpdateTodo,
     responses(
-        (status = 200, description = "Todo updated successfully", body = Todo),
+        (status = 200, description = "Todo updated successfully", body = Todo,
+            headers(("ETag" = String, description = "Version of the updated todo"))),
         (status = 400, description = "Bad request", body = ErrorResponse, 
             example = json!(ErrorResponse { error: "text: length must be between 1 and 200".to_string() })),
+        (status = 401, description = "Unauthorized to access", body = ErrorResponse, example = json!(ErrorResponse { error: "invalid credentials".to_string() })),
+        (status = 403, description = "Role of the user is not allowed to access", body = ErrorResponse, example = json!(ErrorResponse { error: "insufficient role".to_string() })),
         (status = 404, description = "Todo not found"),
+        (status = 412, description = "Todo was modified since the given ETag", body = ErrorResponse),
         (status = 500, description = "Internal error", body = ErrorResponse)
     ),
     params(
         ("id" = String, Path, description = "Todo id"),
+        ("If-Match" = Option<String>, Header, description = "ETag of the todo to update"),
+    ),
+    security(
+        ("basic_auth" = ["editor"]),
+        ("bearer_auth" = ["editor"]),
+        ("api_key" = ["editor"])
     )
 )]
+#[deprecated(note = "use todos_patch or todos_replace")]
 pub async fn todos_update(
+    path: Path<String>,
+    state: State<SharedState>,
+    user: RequireRole<Editor>,
+    if_match: IfMatchVersion,
+    input: ValidatedJson<UpdateTodo>,
+) -> impl IntoResponse {
+    (
+        [DEPRECATION],
+        update(path, state, user, if_match, input).await,
+    )
+}
+
+async fn update(
     Path(id): Path<String>,
     State(state): State<SharedState>,
+    RequireRole(user, _): RequireRole<Editor>,
+    IfMatchVersion(version): IfMatchVersion,
     ValidatedJson(input): ValidatedJson<UpdateTodo>,
-) -> Result<Json<Todo>, AppError> {
+) -> Result<(TypedHeader<ETag>, Json<Todo>), AppError> {
     let todo_id =
         Uuid::parse_str(&id) // validate id is UUID
             .map_err(|_| AppError::BadRequest(format!("id is not valid uuid: {}", id)))?;
-    if input.text.is_none() && input.completed.is_none() {
+    if input.text.is_none()
+        && input.completed.is_none()
+        && input.list_id.is_none()
+        && input.tags.is_none()
+        && input.due_at.is_none()
+        && input.priority.is_none()
+    {
         return Err(AppError::BadRequest(
-            "either text or completed must be present".to_string(),
+            "either text, completed, list_id, tags, due_at or priority must be present".to_string(),
         ));
     }
 
-    let updated_todo = state.db.update(todo_id, input.into()).await?;
+    let updated_todo = state
+        .db
+        .update(&user, todo_id, input.into(), version)
+        .await?;
     let todo: Todo = updated_todo.into();
-    Ok(Json(todo))
+    Ok((TypedHeader(todo_etag(todo.version)), Json(todo)))
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use crate::datasources::database::models::DbTodo;
-    use crate::datasources::database::{DatabaseError, MockDatabase};
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
     use crate::server::domain::errors::ErrorResponse;
     use crate::server::domain::todos::{Todo, UpdateTodo};
     use crate::server::handlers::todos_update::todos_update;
-    use crate::test_utils::{init_router, read_response_body, test_post};
-    use axum::http::StatusCode;
+    use crate::test_utils::{
+        db_todo, init_router, read_response_body, test_post, test_with_headers,
+    };
+    use axum::http::{header, StatusCode};
     use axum::routing::post;
     use uuid::Uuid;
 
     #[tokio::test]
     async fn test_todos_update() {
-        let mut mock_db = MockDatabase::new();
-        mock_db.expect_update().returning(|_, update_todo| {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_update().returning(|_, _, update_todo, _| {
             Ok(DbTodo {
-                id: Uuid::new_v4(),
-                text: update_todo.text.unwrap(),
                 completed: update_todo.completed.unwrap(),
+                ..db_todo(Uuid::new_v4(), "user", &update_todo.text.unwrap())
             })
         });
         let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
@@ -81,42 +134,100 @@ mod tests {
         let update_todo = UpdateTodo {
             text: Some("updated".to_string()),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         let id = Uuid::new_v4().to_string();
         let response = test_post(app, &format!("/todos/{}", id), update_todo).await;
         assert_eq!(response.status(), StatusCode::OK);
 
+        assert_eq!(response.headers()[header::ETAG], "\"1\"");
+
         let todo: Todo = read_response_body(response).await;
         assert_eq!(todo.text, "updated");
         assert!(todo.completed);
     }
 
+    #[tokio::test]
+    async fn test_todos_update_if_match() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_update()
+            .withf(|_, _, _, version| *version == Some(3))
+            .returning(|_, id, update_todo, _| {
+                Ok(DbTodo {
+                    version: 4,
+                    ..db_todo(id, "user", &update_todo.text.unwrap())
+                })
+            });
+        let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let body = serde_json::json!({ "text": "updated" });
+        let response =
+            test_with_headers(app, "POST", &uri, &[("If-Match", "\"3\"")], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], "\"4\"");
+    }
+
+    #[tokio::test]
+    async fn test_todos_update_version_mismatch() {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_update().returning(|_, id, _, version| {
+            Err(DatabaseError::VersionMismatch {
+                id,
+                expected_version: version.unwrap(),
+            })
+        });
+        let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
+
+        let uri = format!("/todos/{}", Uuid::new_v4());
+        let body = serde_json::json!({ "completed": true });
+        let response =
+            test_with_headers(app, "POST", &uri, &[("If-Match", "\"3\"")], Some(body)).await;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "precondition failed");
+    }
+
     #[tokio::test]
     async fn test_todos_update_not_found() {
         let id = Uuid::new_v4();
 
-        let mut mock_db = MockDatabase::new();
+        let mut mock_db = MockTodoRepository::new();
         mock_db
             .expect_update()
-            .returning(move |_, _| Err(DatabaseError::NotFound { id }));
+            .returning(move |_, _, _, _| Err(DatabaseError::NotFound { id }));
         let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
 
         let update_todo = UpdateTodo {
             text: Some("updated".to_string()),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         let response = test_post(app, &format!("/todos/{}", id), update_todo).await;
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["deprecation"], "@1738368000");
     }
 
     #[tokio::test]
     async fn test_todos_update_invalid_id() {
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
 
         let update_todo = UpdateTodo {
             text: Some("updated".to_string()),
             completed: Some(true),
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         let response = test_post(app, &format!("/todos/{}", "invalid"), update_todo).await;
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
@@ -129,7 +240,7 @@ mod tests {
     async fn test_todos_update_invalid_request() {
         let id = Uuid::new_v4();
 
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
 
         #[derive(serde::Serialize)]
@@ -148,12 +259,16 @@ mod tests {
     async fn test_todos_update_empty_changes() {
         let id = Uuid::new_v4();
 
-        let mock_db = MockDatabase::new();
+        let mock_db = MockTodoRepository::new();
         let app = init_router(mock_db, "/todos/{id}", post(todos_update)).await;
 
         let update_todo = UpdateTodo {
             text: None,
             completed: None,
+            list_id: None,
+            tags: None,
+            due_at: None,
+            priority: None,
         };
         let response = test_post(app, &format!("/todos/{}", id), update_todo).await;
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
@@ -161,7 +276,7 @@ mod tests {
         let response_body: ErrorResponse = read_response_body(response).await;
         assert_eq!(
             response_body.error,
-            "either text or completed must be present"
+            "either text, completed, list_id, tags, due_at or priority must be present"
         );
     }
 }
@@ -0,0 +1,166 @@
+use crate::{
+    datasources::database::models::{DbIdempotentResponse, DbNewIdempotencyKey},
+    server::errors::AppError,
+    SharedState,
+};
+use axum::http::StatusCode;
+use chrono::Utc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Keys of requests in progress expire after this time, so a cancelled request does not block
+/// retries until the TTL of the response. Longer than the timeout of the requests.
+const IN_PROGRESS_DURATION: Duration = Duration::from_secs(60);
+
+/// Outcome of reserving an `Idempotency-Key` for a request.
+pub enum Reservation {
+    /// The request is the first with the key and has to be handled.
+    Reserved,
+    /// The request was already handled with this response.
+    Replay { status: StatusCode, body: String },
+}
+
+/// Hash of the request, retries with the same key must have the same hash.
+pub fn request_hash<T: Serialize>(request: &T) -> Result<String, AppError> {
+    let json = serde_json::to_vec(request).map_err(anyhow::Error::from)?;
+    Ok(Sha256::digest(json)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Reserves the key of the user for the request. Fails with `422` if the key was used with
+/// another request and with `409` while the request of the key is in progress.
+pub async fn reserve(
+    state: &SharedState,
+    user: &str,
+    key: &str,
+    request_hash: String,
+) -> Result<Reservation, AppError> {
+    let new_key = DbNewIdempotencyKey {
+        key: key.to_string(),
+        request_hash: request_hash.clone(),
+        expires_at: Utc::now() + IN_PROGRESS_DURATION,
+    };
+    let Some(stored) = state.db.insert_idempotency_key(user, new_key).await? else {
+        return Ok(Reservation::Reserved);
+    };
+
+    if stored.request_hash != request_hash {
+        return Err(AppError::UnprocessableEntity(
+            "Idempotency-Key was already used with another request".to_string(),
+        ));
+    }
+    match (stored.response_status, stored.response_body) {
+        (Some(status), Some(body)) => {
+            let status = u16::try_from(status)
+                .ok()
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .ok_or_else(|| anyhow::anyhow!("stored response has invalid status: {}", status))?;
+            Ok(Reservation::Replay { status, body })
+        }
+        _ => Err(AppError::Conflict(
+            "request with the Idempotency-Key is in progress".to_string(),
+        )),
+    }
+}
+
+/// Stores the response of the request of the key to replay it until the TTL expires.
+pub async fn complete<T: Serialize>(
+    state: &SharedState,
+    user: &str,
+    key: &str,
+    status: StatusCode,
+    body: &T,
+) -> Result<(), AppError> {
+    let response = DbIdempotentResponse {
+        status: i32::from(status.as_u16()),
+        body: serde_json::to_string(body).map_err(anyhow::Error::from)?,
+        expires_at: Utc::now() + state.idempotency_ttl,
+    };
+    state
+        .db
+        .complete_idempotency_key(user, key, response)
+        .await?;
+    Ok(())
+}
+
+/// Releases the key after the request failed, so it can be retried.
+pub async fn release(state: &SharedState, user: &str, key: &str) -> Result<(), AppError> {
+    state.db.remove_idempotency_key(user, key).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasources::database::models::DbIdempotencyKey;
+    use crate::datasources::database::MockTodoRepository;
+    use crate::test_utils::test_state;
+
+    fn stored(request_hash: &str, response: Option<(i32, &str)>) -> DbIdempotencyKey {
+        DbIdempotencyKey {
+            owner: "user".to_string(),
+            key: "key".to_string(),
+            request_hash: request_hash.to_string(),
+            response_status: response.map(|(status, _)| status),
+            response_body: response.map(|(_, body)| body.to_string()),
+            created_at: Utc::now(),
+            expires_at: Utc::now(),
+        }
+    }
+
+    async fn reserve_with(stored: Option<DbIdempotencyKey>) -> Result<Reservation, AppError> {
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_insert_idempotency_key()
+            .withf(|owner, key| owner == "user" && key.key == "key" && key.request_hash == "hash")
+            .returning(move |_, _| Ok(stored.clone()));
+        reserve(&test_state(mock_db), "user", "key", "hash".to_string()).await
+    }
+
+    #[test]
+    fn test_request_hash() {
+        let hash = request_hash(&serde_json::json!({ "text": "a" })).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(
+            hash,
+            request_hash(&serde_json::json!({ "text": "a" })).unwrap()
+        );
+        assert_ne!(
+            hash,
+            request_hash(&serde_json::json!({ "text": "b" })).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reserve() {
+        assert!(matches!(
+            reserve_with(None).await,
+            Ok(Reservation::Reserved)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reserve_replay() {
+        let reservation = reserve_with(Some(stored("hash", Some((201, "{}"))))).await;
+        let Ok(Reservation::Replay { status, body }) = reservation else {
+            panic!("response is not replayed");
+        };
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body, "{}");
+    }
+
+    #[tokio::test]
+    async fn test_reserve_other_request() {
+        let reservation = reserve_with(Some(stored("other", Some((201, "{}"))))).await;
+        assert!(matches!(reservation, Err(AppError::UnprocessableEntity(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reserve_in_progress() {
+        let reservation = reserve_with(Some(stored("hash", None))).await;
+        assert!(matches!(reservation, Err(AppError::Conflict(_))));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::SharedState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+    routing::get,
+    Router,
+};
+use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::time::Instant;
+
+const LATENCY_SECONDS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+fn recorder_builder() -> PrometheusBuilder {
+    // histograms instead of summaries for all latencies, they can be aggregated over instances
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            LATENCY_SECONDS,
+        )
+        .expect("failed to set buckets")
+}
+
+/// Installs the Prometheus recorder as the global recorder, can only be called once.
+pub fn install_recorder() -> PrometheusHandle {
+    let handle = recorder_builder()
+        .install_recorder()
+        .expect("failed to install metrics recorder");
+
+    describe_counter!("http_requests_total", "Requests by matched path");
+    describe_histogram!(
+        "http_requests_duration_seconds",
+        Unit::Seconds,
+        "Latency of requests by matched path"
+    );
+    describe_gauge!(
+        "db_pool_connections",
+        "Connections of the database pool by state"
+    );
+    describe_gauge!(
+        "db_pool_max_connections",
+        "Maximum connections of the database pool"
+    );
+    describe_histogram!(
+        "db_pool_acquire_duration_seconds",
+        Unit::Seconds,
+        "Time waited for a connection of the database pool"
+    );
+    describe_histogram!(
+        "db_query_duration_seconds",
+        Unit::Seconds,
+        "Latency of the database queries by repository method"
+    );
+    handle
+}
+
+/// Router with the `/metrics` endpoint, to be served on its own port so it is not exposed with
+/// the API.
+pub fn new_metrics_router(state: SharedState, handle: PrometheusHandle) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state((state, handle))
+}
+
+async fn render_metrics(State((state, handle)): State<(SharedState, PrometheusHandle)>) -> String {
+    // the pool is only read on scrape instead of on every change
+    if let Some(status) = state.db.pool_status() {
+        metrics::gauge!("db_pool_connections", "state" => "idle").set(status.idle);
+        metrics::gauge!("db_pool_connections", "state" => "in_use")
+            .set(status.size.saturating_sub(status.idle));
+        metrics::gauge!("db_pool_max_connections").set(status.max_size);
+    }
+    handle.render()
+}
+
+/// Counts the requests and records their latency. The path is the matched route, e.g.
+/// `/api/v1/todos/{id}`, or `unmatched` for unknown paths, so clients cannot add labels.
+pub(crate) async fn track_metrics(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(matched_path) => matched_path.as_str().to_owned(),
+        None => "unmatched".to_owned(),
+    };
+    let method = request.method().clone();
+
+    let response = next.run(request).await;
+    let latency = start.elapsed().as_secs_f64();
+
+    let labels = [
+        ("method", method.to_string()),
+        ("path", path),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!("http_requests_duration_seconds", &labels).record(latency);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasources::database::models::DbPoolStatus;
+    use crate::datasources::database::{DatabaseError, MockTodoRepository};
+    use crate::server::rate_limit::{Quota, RateLimiter, RateLimits};
+    use crate::server::routes::new_router;
+    use crate::test_utils::{test_get, test_state};
+    use crate::AppState;
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use std::{sync::Arc, time::Duration};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_metrics() {
+        // the tests run on a single thread, so the recorder sees the requests of the test only
+        let recorder = recorder_builder().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let mut mock_db = MockTodoRepository::new();
+        mock_db
+            .expect_get()
+            .returning(|_, id| Err(DatabaseError::NotFound { id }));
+        mock_db.expect_pool_status().returning(|| {
+            Some(DbPoolStatus {
+                size: 3,
+                idle: 1,
+                max_size: 5,
+            })
+        });
+        let app_state = test_state(mock_db);
+
+        let app = new_router(app_state.clone());
+        let response = test_get(app.clone(), &format!("/api/v1/todos/{}", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = test_get(app, &format!("/api/v1/unknown/{}", Uuid::new_v4())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let metrics_app = new_metrics_router(app_state, handle);
+        let response = test_get(metrics_app, "/metrics").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(
+            r#"http_requests_total{method="GET",path="/api/v1/todos/{id}",status="404"} 1"#
+        ));
+        assert!(body.contains(r#"http_requests_duration_seconds_bucket{method="GET",path="/api/v1/todos/{id}",status="404",le="10"} 1"#));
+        assert!(
+            body.contains(r#"http_requests_total{method="GET",path="unmatched",status="404"} 1"#)
+        );
+        assert!(body.contains(r#"db_pool_connections{state="idle"} 1"#));
+        assert!(body.contains(r#"db_pool_connections{state="in_use"} 2"#));
+        assert!(body.contains("db_pool_max_connections 5"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_rate_limited() {
+        let recorder = recorder_builder().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let app_state = Arc::new(AppState {
+            rate_limiter: RateLimiter::new(RateLimits {
+                api: Some(Quota {
+                    capacity: 1,
+                    period: Duration::from_secs(60),
+                }),
+                ..RateLimits::off()
+            }),
+            ..Arc::into_inner(test_state(MockTodoRepository::new())).unwrap()
+        });
+
+        let app = new_router(app_state.clone());
+        let response = test_get(app.clone(), "/api/v1/protected").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = test_get(app, "/api/v1/protected").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let body = handle.render();
+        assert!(body.contains(
+            r#"http_requests_total{method="GET",path="/api/v1/protected",status="429"} 1"#
+        ));
+    }
+}
@@ -1,6 +1,15 @@
+pub mod api_keys;
+pub mod credentials;
 pub mod domain;
 mod errors;
 mod extractors;
 mod handlers;
+pub mod idempotency;
+pub mod metrics;
 mod openapi;
+mod problem_details;
+pub mod rate_limit;
+pub mod roles;
 pub mod routes;
+pub mod tokens;
+mod trace_context;
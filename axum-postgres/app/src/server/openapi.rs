@@ -1,6 +1,7 @@
+use crate::server::domain::errors::{FieldError, ProblemDetails};
 use axum::Router;
 use utoipa::{
-    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
     Modify, OpenApi,
 };
 use utoipa_axum::router::OpenApiRouter;
@@ -8,33 +9,80 @@ use utoipa_swagger_ui::SwaggerUi;
 
 pub const TODO_TAG: &str = "Todos";
 pub const PROTECTED_TAG: &str = "Protected";
+pub const AUTH_TAG: &str = "Auth";
+pub const ADMIN_TAG: &str = "Admin";
+pub const HEALTH_TAG: &str = "Health";
+pub const LISTS_TAG: &str = "Lists";
 
 #[derive(OpenApi)]
 #[openapi(
-    info(title = "Axum Postgres Todos API", description = "Todos Api description"),
+    info(
+        title = "Axum Postgres Todos API",
+        description = "Todos Api description. Errors have an `ErrorResponse` body, or `ProblemDetails` as `application/problem+json` (RFC 7807) if the `Accept` header prefers it."
+    ),
     modifiers(&SecurityAddon),
+    components(schemas(ProblemDetails, FieldError)),
     tags(
         (name = TODO_TAG, description = "Todos API"),
-        (name = PROTECTED_TAG, description = "Protected API")
+        (name = LISTS_TAG, description = "Lists grouping the todos"),
+        (name = PROTECTED_TAG, description = "Protected API"),
+        (name = AUTH_TAG, description = "Authentication API"),
+        (name = ADMIN_TAG, description = "Administration API, requires the admin role"),
+        (name = HEALTH_TAG, description = "Health checks for orchestrators and load balancers")
     )
 )]
 struct ApiDoc;
 
 struct SecurityAddon;
 
+/// The security requirements of the operations list the minimum role.
+const ROLES_DESCRIPTION: &str =
+    "Users have the role `viewer`, `editor` or `admin`, each role is allowed everything the lower roles are.";
+
 impl Modify for SecurityAddon {
     fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
         if let Some(components) = openapi.components.as_mut() {
             components.add_security_scheme(
                 "basic_auth",
-                SecurityScheme::Http(Http::new(HttpAuthScheme::Basic)),
-            )
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Basic)
+                        .description(Some(ROLES_DESCRIPTION))
+                        .build(),
+                ),
+            );
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .description(Some(format!(
+                            "Access token from `POST /api/v1/auth/token`. {}",
+                            ROLES_DESCRIPTION
+                        )))
+                        .build(),
+                ),
+            );
+            components.add_security_scheme(
+                "api_key",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                    "X-API-Key",
+                    &format!(
+                        "Key from `POST /api/v1/auth/keys`, the role of the key is capped by the role of its owner. {}",
+                        ROLES_DESCRIPTION
+                    ),
+                ))),
+            );
         }
     }
 }
 
-pub fn new_openapi_router() -> Router {
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi()).split_for_parts();
+/// Serves the routes with the Swagger UI of their OpenAPI document.
+pub fn new_openapi_router(routes: OpenApiRouter) -> Router {
+    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .merge(routes)
+        .split_for_parts();
 
     router.merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api))
 }
@@ -48,7 +96,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_new_server() {
-        let app = new_openapi_router();
+        let app = new_openapi_router(OpenApiRouter::new());
 
         let response = test_get(app, "/swagger-ui").await;
         assert_eq!(response.status(), StatusCode::SEE_OTHER);
@@ -0,0 +1,166 @@
+use crate::server::{
+    domain::errors::ProblemDetails, errors::ErrorDetails, routes::REQUEST_ID_HEADER,
+};
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{
+        header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Renders error responses as problem details for requests preferring `application/problem+json`
+/// to `application/json`, other requests keep the `ErrorResponse` body.
+pub async fn problem_details(request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    let wants_problem = headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(prefers_problem_json);
+    let instance = headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|request_id| request_id.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(request).await;
+    if !wants_problem {
+        return response;
+    }
+    let Some(details) = response.extensions().get::<ErrorDetails>().cloned() else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    let problem = ProblemDetails {
+        problem_type: "about:blank".to_string(),
+        title: parts
+            .status
+            .canonical_reason()
+            .unwrap_or_default()
+            .to_string(),
+        status: parts.status.as_u16(),
+        detail: details.detail,
+        instance,
+        errors: details.errors,
+    };
+    let body = match serde_json::to_vec(&problem) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to serialize problem details: {:?}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    parts
+        .headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    parts.headers.remove(CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Whether the `Accept` header ranks `application/problem+json` at least as high as
+/// `application/json`, wildcards are ignored.
+fn prefers_problem_json(accept: &str) -> bool {
+    let mut problem_quality = 0.0;
+    let mut json_quality = 0.0;
+    for media_range in accept.split(',') {
+        let mut params = media_range.split(';').map(str::trim);
+        let media_type = params.next().unwrap_or_default();
+        let quality = params
+            .filter_map(|param| param.strip_prefix("q="))
+            .find_map(|quality| quality.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if media_type.eq_ignore_ascii_case(PROBLEM_JSON) {
+            problem_quality = quality;
+        } else if media_type.eq_ignore_ascii_case("application/json") {
+            json_quality = quality;
+        }
+    }
+    problem_quality > 0.0 && problem_quality >= json_quality
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::domain::errors::{ErrorResponse, FieldError};
+    use crate::server::errors::AppError;
+    use crate::test_utils::read_response_body;
+    use axum::{http::StatusCode, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn invalid() -> Result<(), AppError> {
+        let mut errors = validator::ValidationErrors::new();
+        errors.add(
+            "text",
+            validator::ValidationError::new("length").with_message("too long".into()),
+        );
+        Err(AppError::ValidationError(errors))
+    }
+
+    async fn request(accept: Option<&str>) -> Response {
+        let app = Router::new()
+            .route("/invalid", get(invalid))
+            .layer(middleware::from_fn(problem_details));
+        let mut request = Request::builder()
+            .uri("/invalid")
+            .header(REQUEST_ID_HEADER, "request-1");
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prefers_problem_json() {
+        assert!(prefers_problem_json("application/problem+json"));
+        assert!(prefers_problem_json(
+            "application/json;q=0.5, application/problem+json"
+        ));
+        assert!(prefers_problem_json(
+            "application/problem+json, application/json"
+        ));
+        assert!(!prefers_problem_json("application/json"));
+        assert!(!prefers_problem_json("*/*"));
+        assert!(!prefers_problem_json(
+            "application/problem+json;q=0.5, application/json"
+        ));
+        assert!(!prefers_problem_json("application/problem+json;q=0"));
+    }
+
+    #[tokio::test]
+    async fn test_problem_details() {
+        let response = request(Some(PROBLEM_JSON)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON);
+
+        let problem: ProblemDetails = read_response_body(response).await;
+        assert_eq!(problem.problem_type, "about:blank");
+        assert_eq!(problem.title, "Bad Request");
+        assert_eq!(problem.status, 400);
+        assert_eq!(problem.detail, "text: too long");
+        assert_eq!(problem.instance.as_deref(), Some("request-1"));
+        assert_eq!(
+            problem.errors,
+            vec![FieldError {
+                field: "text".to_string(),
+                code: "length".to_string(),
+                message: "too long".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_response_by_default() {
+        let response = request(None).await;
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "text: too long");
+    }
+}
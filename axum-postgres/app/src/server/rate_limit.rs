@@ -0,0 +1,385 @@
+use crate::server::extractors::auth_api_key::XApiKey;
+use crate::server::{api_keys::hash_api_key, errors::AppError};
+use crate::SharedState;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::headers::{authorization::Bearer, Authorization, HeaderMapExt};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Routes sharing a bucket per client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    /// `/api/v1/auth`, tokens and API keys
+    Auth,
+    /// `/api/v1/admin`
+    Admin,
+    /// The rest of `/api`
+    Api,
+}
+
+impl RouteGroup {
+    /// Group of the path, `None` for paths which are not limited like `/status` and the docs.
+    pub fn of(path: &str) -> Option<Self> {
+        if path.starts_with("/api/v1/auth") {
+            Some(RouteGroup::Auth)
+        } else if path.starts_with("/api/v1/admin") {
+            Some(RouteGroup::Admin)
+        } else if path.starts_with("/api/") {
+            Some(RouteGroup::Api)
+        } else {
+            None
+        }
+    }
+}
+
+/// Token bucket of `capacity` requests, refilled completely during `period`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    /// Parses `<requests>/<seconds>`, e.g. `100/60`, or `off` for no limit.
+    pub fn parse(value: &str) -> Result<Option<Self>, String> {
+        if value == "off" {
+            return Ok(None);
+        }
+        let (capacity, seconds) = value
+            .split_once('/')
+            .ok_or_else(|| format!("rate limit must be <requests>/<seconds> or off: {}", value))?;
+        let capacity: u32 = capacity
+            .parse()
+            .map_err(|_| format!("requests of rate limit must be a number: {}", value))?;
+        let seconds: u64 = seconds
+            .parse()
+            .map_err(|_| format!("seconds of rate limit must be a number: {}", value))?;
+        if capacity == 0 || seconds == 0 {
+            return Err(format!("rate limit must be greater than zero: {}", value));
+        }
+        Ok(Some(Quota {
+            capacity,
+            period: Duration::from_secs(seconds),
+        }))
+    }
+
+    fn tokens_per_second(&self) -> f64 {
+        f64::from(self.capacity) / self.period.as_secs_f64()
+    }
+}
+
+/// Quotas of the route groups, `None` for no limit.
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub auth: Option<Quota>,
+    pub admin: Option<Quota>,
+    pub api: Option<Quota>,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        let quota = |capacity, seconds| {
+            Some(Quota {
+                capacity,
+                period: Duration::from_secs(seconds),
+            })
+        };
+        RateLimits {
+            auth: quota(20, 60),
+            admin: quota(60, 60),
+            api: quota(300, 60),
+        }
+    }
+}
+
+impl RateLimits {
+    /// Limits without any quota.
+    pub fn off() -> Self {
+        RateLimits {
+            auth: None,
+            admin: None,
+            api: None,
+        }
+    }
+
+    fn quota(&self, group: RouteGroup) -> Option<Quota> {
+        match group {
+            RouteGroup::Auth => self.auth,
+            RouteGroup::Admin => self.admin,
+            RouteGroup::Api => self.api,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Outcome of taking a token, with the values of the `RateLimit-*` headers.
+#[derive(Debug, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset: u64,
+    /// Seconds until the next token, when the request is not allowed.
+    pub retry_after: u64,
+}
+
+/// Buckets below this size are not cleaned up.
+const MIN_CLEANUP_SIZE: usize = 1024;
+
+/// Token buckets per route group and client, kept in memory of the instance.
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    buckets: HashMap<(RouteGroup, String), Bucket>,
+    cleanup_at: usize,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        RateLimiter {
+            limits,
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                cleanup_at: MIN_CLEANUP_SIZE,
+            }),
+        }
+    }
+
+    /// Takes a token of the client from the bucket of the group, `None` if the group has no limit.
+    pub fn check(&self, group: RouteGroup, client: &str) -> Option<Decision> {
+        self.check_at(group, client, Instant::now())
+    }
+
+    fn check_at(&self, group: RouteGroup, client: &str, now: Instant) -> Option<Decision> {
+        let quota = self.limits.quota(group)?;
+        let capacity = f64::from(quota.capacity);
+        let rate = quota.tokens_per_second();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.buckets.len() >= buckets.cleanup_at {
+            // full buckets are the same as new ones
+            buckets.buckets.retain(|(group, _), bucket| {
+                self.limits.quota(*group).is_some_and(|quota| {
+                    let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                    bucket.tokens + elapsed * quota.tokens_per_second() < f64::from(quota.capacity)
+                })
+            });
+            buckets.cleanup_at = (buckets.buckets.len() * 2).max(MIN_CLEANUP_SIZE);
+        }
+
+        let bucket = buckets
+            .buckets
+            .entry((group, client.to_string()))
+            .or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+            });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        Some(Decision {
+            allowed,
+            limit: quota.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((capacity - bucket.tokens) / rate).ceil() as u64,
+            retry_after: if allowed {
+                0
+            } else {
+                ((1.0 - bucket.tokens) / rate).ceil() as u64
+            },
+        })
+    }
+}
+
+/// Limits the requests of a route group per client, see `client_of`. The limit is checked before
+/// the credentials, so requests over the limit do not cost a password verification.
+pub async fn rate_limit(
+    State(state): State<SharedState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(group) = RouteGroup::of(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let address = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let client = client_of(request.headers(), address, &state).await;
+    let Some(decision) = state.rate_limiter.check(group, &client) else {
+        return next.run(request).await;
+    };
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        tracing::warn!("Rate limit of {:?} exceeded by {}", group, client);
+        let mut response =
+            AppError::TooManyRequests("too many requests".to_string()).into_response();
+        insert_header(response.headers_mut(), "retry-after", decision.retry_after);
+        response
+    };
+
+    let headers = response.headers_mut();
+    insert_header(headers, "ratelimit-limit", decision.limit);
+    insert_header(headers, "ratelimit-remaining", decision.remaining);
+    insert_header(headers, "ratelimit-reset", decision.reset);
+    response
+}
+
+/// Client of the request, found only with cheap checks: the user of an access token with a valid
+/// signature, an API key whose hash is stored, or else the IP address of the connection. Basic
+/// credentials are only verified by the handlers, so they get the bucket of the address, else
+/// anyone could use up the quota of a user by naming them.
+async fn client_of(headers: &HeaderMap, address: Option<IpAddr>, state: &SharedState) -> String {
+    if let Some(XApiKey(key)) = headers.typed_get::<XApiKey>() {
+        if let Ok(Some(api_key)) = state.db.find_api_key(&hash_api_key(&key)).await {
+            return format!("key:{}", api_key.id);
+        }
+    } else if let Some(Authorization(bearer)) = headers.typed_get::<Authorization<Bearer>>() {
+        if let Ok(claims) = state.tokens.validate(bearer.token()) {
+            return format!("user:{}", claims.sub);
+        }
+    }
+    match address {
+        Some(ip) => format!("ip:{}", ip),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+fn insert_header(headers: &mut HeaderMap, name: &'static str, value: impl Into<u64>) {
+    headers.insert(name, HeaderValue::from(value.into()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(capacity: u32, seconds: u64) -> RateLimiter {
+        RateLimiter::new(RateLimits {
+            api: Some(Quota {
+                capacity,
+                period: Duration::from_secs(seconds),
+            }),
+            ..RateLimits::off()
+        })
+    }
+
+    #[test]
+    fn test_route_group_of() {
+        assert_eq!(RouteGroup::of("/api/v1/auth/token"), Some(RouteGroup::Auth));
+        assert_eq!(
+            RouteGroup::of("/api/v1/admin/users/user/todos"),
+            Some(RouteGroup::Admin)
+        );
+        assert_eq!(RouteGroup::of("/api/v1/todos"), Some(RouteGroup::Api));
+        assert_eq!(RouteGroup::of("/status"), None);
+        assert_eq!(RouteGroup::of("/swagger-ui/"), None);
+    }
+
+    #[test]
+    fn test_quota_parse() {
+        assert_eq!(
+            Quota::parse("100/60"),
+            Ok(Some(Quota {
+                capacity: 100,
+                period: Duration::from_secs(60)
+            }))
+        );
+        assert_eq!(Quota::parse("off"), Ok(None));
+        assert!(Quota::parse("100").is_err());
+        assert!(Quota::parse("a/60").is_err());
+        assert!(Quota::parse("100/0").is_err());
+    }
+
+    #[test]
+    fn test_check_exhausts_bucket() {
+        let limiter = limiter(2, 10);
+        let now = Instant::now();
+
+        let first = limiter.check_at(RouteGroup::Api, "user:a", now).unwrap();
+        assert!(first.allowed);
+        assert_eq!((first.limit, first.remaining, first.reset), (2, 1, 5));
+
+        let second = limiter.check_at(RouteGroup::Api, "user:a", now).unwrap();
+        assert!(second.allowed);
+        assert_eq!(second.remaining, 0);
+
+        let third = limiter.check_at(RouteGroup::Api, "user:a", now).unwrap();
+        assert!(!third.allowed);
+        assert_eq!(
+            (third.remaining, third.reset, third.retry_after),
+            (0, 10, 5)
+        );
+
+        // other clients have their own bucket
+        assert!(
+            limiter
+                .check_at(RouteGroup::Api, "user:b", now)
+                .unwrap()
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_check_refills_bucket() {
+        let limiter = limiter(2, 10);
+        let now = Instant::now();
+        limiter.check_at(RouteGroup::Api, "ip:127.0.0.1", now);
+        limiter.check_at(RouteGroup::Api, "ip:127.0.0.1", now);
+
+        let later = now + Duration::from_secs(5);
+        let decision = limiter
+            .check_at(RouteGroup::Api, "ip:127.0.0.1", later)
+            .unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        let much_later = later + Duration::from_secs(60);
+        let decision = limiter
+            .check_at(RouteGroup::Api, "ip:127.0.0.1", much_later)
+            .unwrap();
+        assert_eq!(decision.remaining, 1);
+    }
+
+    #[test]
+    fn test_check_without_quota() {
+        let limiter = limiter(1, 10);
+        assert_eq!(limiter.check(RouteGroup::Auth, "user:a"), None);
+    }
+
+    #[test]
+    fn test_check_cleans_up_full_buckets() {
+        let limiter = limiter(1, 1);
+        let now = Instant::now();
+        for client in 0..MIN_CLEANUP_SIZE {
+            limiter.check_at(RouteGroup::Api, &client.to_string(), now);
+        }
+
+        limiter.check_at(RouteGroup::Api, "new", now + Duration::from_secs(2));
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.buckets.len(), 1);
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use utoipa::ToSchema;
+
+/// Role of a user, each role is allowed everything the lower roles are.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Reads own todos.
+    Viewer,
+    /// Reads and changes own todos, the role of users configured without one.
+    #[default]
+    Editor,
+    /// Also manages the todos of all users.
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("role must be viewer, editor or admin: {}", s)),
+        }
+    }
+}
+
+/// Minimum role of a `RequireRole` guard.
+pub trait RequiredRole: Send + Sync + 'static {
+    const ROLE: Role;
+}
+
+pub struct Viewer;
+pub struct Editor;
+pub struct Admin;
+
+impl RequiredRole for Viewer {
+    const ROLE: Role = Role::Viewer;
+}
+
+impl RequiredRole for Editor {
+    const ROLE: Role = Role::Editor;
+}
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_order() {
+        assert!(Role::Admin > Role::Editor);
+        assert!(Role::Editor > Role::Viewer);
+        assert_eq!(Role::default(), Role::Editor);
+    }
+
+    #[test]
+    fn test_role_parse() {
+        for role in [Role::Viewer, Role::Editor, Role::Admin] {
+            assert_eq!(role.as_str().parse::<Role>().unwrap(), role);
+        }
+        assert_eq!(
+            "root".parse::<Role>().unwrap_err(),
+            "role must be viewer, editor or admin: root"
+        );
+    }
+}
@@ -1,10 +1,23 @@
 use super::openapi::new_openapi_router;
 use crate::{
-    server::handlers::{protected, todos_create, todos_delete, todos_list, todos_update},
+    server::{
+        handlers::{
+            admin_todos_list, admin_todos_purge, api_keys_create, api_keys_list, api_keys_revoke,
+            auth_token, health_live, health_ready, lists_create, lists_delete, lists_get,
+            lists_list, lists_todos, lists_update, protected, todos_batch, todos_create,
+            todos_delete, todos_get, todos_list, todos_overdue, todos_patch, todos_replace,
+            todos_update,
+        },
+        metrics::track_metrics,
+        problem_details::problem_details,
+        rate_limit::rate_limit,
+        trace_context::{inject_trace_context, set_remote_parent},
+    },
     SharedState,
 };
 use axum::{
-    http::{HeaderName, Request},
+    http::{HeaderName, Request, StatusCode},
+    middleware,
     routing::get,
     Router,
 };
@@ -18,10 +31,10 @@ use tower_http::{
 use tracing::{error, info_span};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-const REQUEST_ID_HEADER: &str = "x-request-id";
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
 
 pub fn new_router(app_state: SharedState) -> Router {
-    new_openapi_router().merge(add_routes(app_state))
+    new_openapi_router(add_routes(app_state))
 }
 
 fn add_routes(app_state: SharedState) -> OpenApiRouter {
@@ -41,54 +54,112 @@ fn add_routes(app_state: SharedState) -> OpenApiRouter {
                     // user is added if the request is authenticated
                     let user = tracing::field::Empty;
 
-                    if let Some(request_id) = request_id {
+                    let span = if let Some(request_id) = request_id {
                         info_span!("http_request", request_id = ?request_id, %method, %uri, user)
                     } else {
                         error!("could not extract request_id");
                         info_span!("http_request", %method, %uri, user)
-                    }
+                    };
+                    set_remote_parent(&span, req.headers());
+                    span
                 })
                 // By default `TraceLayer` will log 5xx responses but we're doing our specific
                 // logging of errors so disable that
                 .on_failure(()),
         )
         .layer(PropagateRequestIdLayer::new(x_request_id))
-        .layer(TimeoutLayer::new(Duration::from_secs(30)));
+        .layer(middleware::from_fn(inject_trace_context))
+        .layer(TimeoutLayer::new(Duration::from_secs(30)))
+        .layer(middleware::from_fn(problem_details))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            rate_limit,
+        ));
 
+    // POST on a todo is kept as a deprecated alias of PATCH
+    #[allow(deprecated)]
     let todos_api_routes = OpenApiRouter::new()
         .routes(routes!(todos_list::todos_list, todos_create::todos_create))
+        .routes(routes!(todos_batch::todos_batch))
+        .routes(routes!(todos_overdue::todos_overdue))
         .routes(routes!(
+            todos_get::todos_get,
+            todos_replace::todos_replace,
+            todos_patch::todos_patch,
             todos_update::todos_update,
             todos_delete::todos_delete
         ));
 
+    let lists_api_routes = OpenApiRouter::new()
+        .routes(routes!(lists_list::lists_list, lists_create::lists_create))
+        .routes(routes!(
+            lists_get::lists_get,
+            lists_update::lists_update,
+            lists_delete::lists_delete
+        ))
+        .routes(routes!(lists_todos::lists_todos));
+
     let protected_routes = OpenApiRouter::new().routes(routes!(protected::protected));
 
+    let auth_routes = OpenApiRouter::new()
+        .routes(routes!(auth_token::auth_token))
+        .routes(routes!(
+            api_keys_list::api_keys_list,
+            api_keys_create::api_keys_create
+        ))
+        .routes(routes!(api_keys_revoke::api_keys_revoke));
+
+    let health_routes = OpenApiRouter::new()
+        .routes(routes!(health_live::health_live))
+        .routes(routes!(health_ready::health_ready));
+
+    let admin_routes = OpenApiRouter::new().routes(routes!(
+        admin_todos_list::admin_todos_list,
+        admin_todos_purge::admin_todos_purge
+    ));
+
     OpenApiRouter::new()
         .route("/status", get(|| async { "OK" }))
+        .nest("/health", health_routes)
         .nest("/api/v1/todos", todos_api_routes)
+        .nest("/api/v1/lists", lists_api_routes)
         .nest("/api/v1/protected", protected_routes)
+        .nest("/api/v1/auth", auth_routes)
+        .nest("/api/v1/admin", admin_routes)
+        // unknown paths go through the middleware too, the default fallback would skip it
+        .fallback(|| async { StatusCode::NOT_FOUND })
         .layer(middleware)
+        // outside of the other middleware, so that rejected requests like 429 are counted too
+        .layer(middleware::from_fn(track_metrics))
         .with_state(app_state)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::datasources::database::{Database, MockDatabase};
-    use crate::test_utils::test_get;
+    use crate::datasources::database::{models::DbApiKey, MockTodoRepository};
+    use crate::server::api_keys::hash_api_key;
+    use crate::server::credentials::Credentials;
+    use crate::server::domain::errors::ErrorResponse;
+    use crate::server::rate_limit::{Quota, RateLimiter, RateLimits};
+    use crate::test_utils::{
+        read_response_body, test_authenticated, test_bearer, test_credentials, test_get,
+        test_state, test_tokens,
+    };
     use crate::AppState;
-    use axum::body::to_bytes;
+    use axum::body::{to_bytes, Body};
     use axum::http::StatusCode;
     use axum::Router;
-    use std::sync::Arc;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use chrono::Utc;
+    use serde_json::json;
+    use std::sync::{atomic::AtomicBool, Arc};
+    use tower::ServiceExt;
+    use uuid::Uuid;
 
     #[tokio::test]
     async fn test_status_endpoint() {
-        let app_state = Arc::new(AppState {
-            db: Database::Mock(MockDatabase::new()),
-            credentials: vec![("user".to_string(), "password".to_string())],
-        });
+        let app_state = test_state(MockTodoRepository::new());
         let app: Router = add_routes(app_state).into();
 
         let response = test_get(app, "/status").await;
@@ -97,4 +168,164 @@ mod tests {
         let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
         assert_eq!(body, "OK");
     }
+    #[tokio::test]
+    async fn test_openapi_roles() {
+        let app_state = test_state(MockTodoRepository::new());
+        let app = new_router(app_state);
+
+        let response = test_get(app, "/api-docs/openapi.json").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let api: serde_json::Value = read_response_body(response).await;
+        let security = |path: &str, method: &str| api["paths"][path][method]["security"].clone();
+        assert_eq!(
+            security("/api/v1/admin/users/{user}/todos", "delete"),
+            json!([{ "basic_auth": ["admin"] }, { "bearer_auth": ["admin"] }, { "api_key": ["admin"] }])
+        );
+        assert_eq!(
+            security("/api/v1/todos", "post"),
+            json!([{ "basic_auth": ["editor"] }, { "bearer_auth": ["editor"] }, { "api_key": ["editor"] }])
+        );
+        assert_eq!(
+            security("/api/v1/todos", "get"),
+            json!([{ "basic_auth": ["viewer"] }, { "bearer_auth": ["viewer"] }, { "api_key": ["viewer"] }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let app_state = Arc::new(AppState {
+            db: Arc::new(MockTodoRepository::new()),
+            credentials: test_credentials(),
+            tokens: test_tokens(),
+            rate_limiter: RateLimiter::new(RateLimits {
+                api: Some(Quota {
+                    capacity: 1,
+                    period: Duration::from_secs(60),
+                }),
+                ..RateLimits::off()
+            }),
+            idempotency_ttl: Duration::from_secs(60),
+            shutting_down: AtomicBool::new(false),
+        });
+        let app: Router = add_routes(app_state).into();
+
+        let response = test_get(app.clone(), "/api/v1/protected").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["ratelimit-limit"], "1");
+        assert_eq!(response.headers()["ratelimit-remaining"], "0");
+        assert_eq!(response.headers()["ratelimit-reset"], "60");
+
+        let response = test_get(app.clone(), "/api/v1/protected").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "60");
+        let response_body: ErrorResponse = read_response_body(response).await;
+        assert_eq!(response_body.error, "too many requests");
+
+        // the bucket is per user
+        let response = test_authenticated(
+            app.clone(),
+            "/api/v1/protected",
+            "GET",
+            &test_bearer("admin"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // other route groups and the status are not limited
+        let response = test_get(app, "/status").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("ratelimit-limit"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_before_credentials() {
+        // verifying a bcrypt hash of cost 16 takes far longer than the timeout
+        let slow_hash = "$2b$16$driJ439QcPI8nc6H4iHNjO7PIHf70yjjL0aFiT0c9xlbrwaGiayxO";
+        let app_state = Arc::new(AppState {
+            db: Arc::new(MockTodoRepository::new()),
+            credentials: Credentials::parse(&format!("slow:{}", slow_hash)).unwrap(),
+            tokens: test_tokens(),
+            rate_limiter: RateLimiter::new(RateLimits {
+                api: Some(Quota {
+                    capacity: 1,
+                    period: Duration::from_secs(60),
+                }),
+                ..RateLimits::off()
+            }),
+            idempotency_ttl: Duration::from_secs(60),
+            shutting_down: AtomicBool::new(false),
+        });
+        let app: Router = add_routes(app_state).into();
+
+        // uses up the bucket of the address
+        let response = test_authenticated(app.clone(), "/api/v1/protected", "GET", "").await;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // unverified credentials get the bucket of the address, not the one of the user they name
+        let header = format!("Basic {}", STANDARD.encode("slow:pass"));
+        let response = tokio::time::timeout(
+            Duration::from_secs(5),
+            test_authenticated(app.clone(), "/api/v1/protected", "GET", &header),
+        )
+        .await
+        .expect("credentials were verified before the rate limit");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let response =
+            test_authenticated(app, "/api/v1/protected", "GET", &test_bearer("slow")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_api_key() {
+        let key_id = Uuid::new_v4();
+        let mut mock_db = MockTodoRepository::new();
+        mock_db.expect_find_api_key().returning(move |key_hash| {
+            Ok((key_hash == hash_api_key("tdk_valid")).then(|| DbApiKey {
+                id: key_id,
+                owner: "user".to_string(),
+                name: "batch job".to_string(),
+                role: "viewer".to_string(),
+                prefix: "tdk_vali".to_string(),
+                created_at: Utc::now(),
+                revoked_at: None,
+            }))
+        });
+        let app_state = Arc::new(AppState {
+            db: Arc::new(mock_db),
+            credentials: test_credentials(),
+            tokens: test_tokens(),
+            rate_limiter: RateLimiter::new(RateLimits {
+                api: Some(Quota {
+                    capacity: 1,
+                    period: Duration::from_secs(60),
+                }),
+                ..RateLimits::off()
+            }),
+            idempotency_ttl: Duration::from_secs(60),
+            shutting_down: AtomicBool::new(false),
+        });
+        let app: Router = add_routes(app_state).into();
+
+        let api_key = |app: &Router, key: &'static str| {
+            let request = Request::builder()
+                .uri("/api/v1/protected")
+                .header("x-api-key", key)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        // unknown keys get the bucket of the address
+        let response = api_key(&app, "tdk_unknown").await.unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = api_key(&app, "tdk_other").await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // a stored key gets its own bucket
+        let response = api_key(&app, "tdk_valid").await.unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = api_key(&app, "tdk_valid").await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
 }
@@ -0,0 +1,129 @@
+use crate::server::roles::Role;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub const MIN_SECRET_LENGTH: usize = 32;
+
+/// Claims of the access tokens, the user is the subject.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    /// Role of the user when the token was issued.
+    pub role: Role,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Issues and validates access tokens, JWTs signed with HS256.
+pub struct TokenKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    validation: Validation,
+    expiration: Duration,
+}
+
+impl TokenKeys {
+    pub fn new(secret: &[u8], expiration: Duration) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_required_spec_claims(&["exp", "sub"]);
+        // tokens are issued and validated by the same clock
+        validation.leeway = 0;
+        TokenKeys {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            validation,
+            expiration,
+        }
+    }
+
+    /// Keys with a random secret, tokens are only valid for this process.
+    pub fn random(expiration: Duration) -> Self {
+        let mut secret = [0u8; MIN_SECRET_LENGTH];
+        OsRng.fill_bytes(&mut secret);
+        TokenKeys::new(&secret, expiration)
+    }
+
+    /// Time after which issued tokens expire.
+    pub fn expiration(&self) -> Duration {
+        self.expiration
+    }
+
+    pub fn issue(&self, user: &str, role: Role) -> anyhow::Result<String> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: user.to_string(),
+            role,
+            iat: now,
+            exp: now.saturating_add(self.expiration.as_secs().try_into().unwrap_or(i64::MAX)),
+        };
+        Ok(jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &self.encoding,
+        )?)
+    }
+
+    /// Fails if the token is not signed with these keys or has expired.
+    pub fn validate(&self, token: &str) -> anyhow::Result<Claims> {
+        let data = jsonwebtoken::decode::<Claims>(token, &self.decoding, &self.validation)?;
+        Ok(data.claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_issue_and_validate() {
+        let keys = TokenKeys::new(SECRET, Duration::from_secs(60));
+        let token = keys.issue("user", Role::Viewer).unwrap();
+
+        let claims = keys.validate(&token).unwrap();
+        assert_eq!(claims.sub, "user");
+        assert_eq!(claims.role, Role::Viewer);
+        assert_eq!(claims.exp - claims.iat, 60);
+    }
+
+    #[test]
+    fn test_validate_other_secret() {
+        let token = TokenKeys::new(SECRET, Duration::from_secs(60))
+            .issue("user", Role::Editor)
+            .unwrap();
+
+        let other_keys = TokenKeys::new(b"another secret of at least 32 bytes", Duration::ZERO);
+        assert!(other_keys.validate(&token).is_err());
+        assert!(TokenKeys::random(Duration::ZERO).validate(&token).is_err());
+    }
+
+    #[test]
+    fn test_validate_expired() {
+        let keys = TokenKeys::new(SECRET, Duration::from_secs(60));
+        let now = Utc::now().timestamp();
+        let expired = Claims {
+            sub: "user".to_string(),
+            role: Role::Editor,
+            iat: now - 120,
+            exp: now - 60,
+        };
+        let token = jsonwebtoken::encode(&Header::default(), &expired, &keys.encoding).unwrap();
+        assert!(keys.validate(&token).is_err());
+    }
+
+    #[test]
+    fn test_validate_malformed() {
+        let keys = TokenKeys::new(SECRET, Duration::from_secs(60));
+        assert!(keys.validate("").is_err());
+        assert!(keys.validate("not.a.token").is_err());
+
+        // unsigned tokens are rejected
+        let token = keys.issue("user", Role::Editor).unwrap();
+        let unsigned = format!("{}.", token.rsplit_once('.').unwrap().0);
+        assert!(keys.validate(&unsigned).is_err());
+    }
+}
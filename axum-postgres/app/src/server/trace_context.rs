@@ -0,0 +1,86 @@
+use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
+use opentelemetry::global;
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Continues the trace of the `traceparent` header of the request in the span. Does nothing
+/// unless OpenTelemetry is enabled, which installs the W3C trace context propagator.
+pub(crate) fn set_remote_parent(span: &Span, headers: &HeaderMap) {
+    let parent =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(parent);
+}
+
+/// Adds the `traceparent` of the request span to the response, so clients can look up the trace
+/// of their request.
+pub(crate) async fn inject_trace_context(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(response.headers_mut()))
+    });
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datasources::database::MockTodoRepository;
+    use crate::server::routes::new_router;
+    use crate::test_utils::{test_state, test_with_headers};
+    use axum::http::StatusCode;
+    use opentelemetry::trace::{SpanId, TraceId, TracerProvider};
+    use opentelemetry::{global, KeyValue};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use tracing_subscriber::prelude::*;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const PARENT_ID: &str = "00f067aa0ba902b7";
+
+    #[tokio::test]
+    async fn test_trace_context() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        // the tests run on a single thread, so the subscriber sees the request of the test only
+        let _guard = tracing::subscriber::set_default(subscriber);
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let app = new_router(test_state(MockTodoRepository::new()));
+        let traceparent = format!("00-{}-{}-01", TRACE_ID, PARENT_ID);
+        let response = test_with_headers(
+            app,
+            "GET",
+            "/status",
+            &[("traceparent", traceparent.as_str())],
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the response continues the trace of the request
+        let returned = response.headers()["traceparent"].to_str().unwrap();
+        assert!(returned.starts_with(&format!("00-{}-", TRACE_ID)));
+        assert!(!returned.contains(PARENT_ID));
+        // the span ends with the response
+        drop(response);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let span = spans
+            .iter()
+            .find(|span| span.name == "http_request")
+            .unwrap();
+        assert_eq!(
+            span.span_context.trace_id(),
+            TraceId::from_hex(TRACE_ID).unwrap()
+        );
+        assert_eq!(span.parent_span_id, SpanId::from_hex(PARENT_ID).unwrap());
+        assert!(span
+            .attributes
+            .contains(&KeyValue::new("method", "GET".to_string())));
+    }
+}
@@ -0,0 +1,68 @@
+use opentelemetry::global;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+
+/// Tracer provider exporting spans in batches with OTLP over HTTP to the collector at `endpoint`,
+/// e.g. `http://localhost:4318`. Also enables the propagation of W3C `traceparent` headers.
+pub fn init_tracer_provider(endpoint: &str, service_name: String) -> SdkTracerProvider {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .expect("failed to create OTLP exporter");
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    provider
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, extract::State, routing::post, Router};
+    use opentelemetry::trace::TracerProvider;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::prelude::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_init_tracer_provider_exports_to_collector() {
+        // stand-in of a collector which keeps the exported batches
+        let received = Arc::new(Mutex::new(Vec::<Bytes>::new()));
+        let collector = Router::new()
+            .route(
+                "/v1/traces",
+                post(
+                    |State(received): State<Arc<Mutex<Vec<Bytes>>>>, body: Bytes| async move {
+                        received.lock().expect("lock poisoned").push(body);
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind collector");
+        let address = listener.local_addr().expect("no collector address");
+        tokio::spawn(async move { axum::serve(listener, collector).await });
+
+        let provider =
+            init_tracer_provider(&format!("http://{}/", address), "todos-test".to_string());
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("get_values", db.system = "postgresql").entered();
+        });
+        // the export blocks until the collector answered
+        tokio::task::spawn_blocking(move || provider.force_flush())
+            .await
+            .expect("flush panicked")
+            .expect("flush failed");
+
+        let received = received.lock().expect("lock poisoned");
+        assert_eq!(received.len(), 1);
+        let contains = |value: &[u8]| received[0].windows(value.len()).any(|w| w == value);
+        assert!(contains(b"get_values"));
+        assert!(contains(b"todos-test"));
+    }
+}
@@ -1,5 +1,11 @@
 use crate::{
-    datasources::database::{Database, MockDatabase},
+    datasources::database::{models::DbTodo, MockTodoRepository},
+    server::{
+        credentials::Credentials,
+        rate_limit::{RateLimiter, RateLimits},
+        roles::Role,
+        tokens::TokenKeys,
+    },
     AppState, SharedState,
 };
 use axum::{
@@ -9,26 +15,82 @@ use axum::{
     routing::MethodRouter,
     Router,
 };
-use std::sync::Arc;
+use chrono::Utc;
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 use tower::ServiceExt;
+use uuid::Uuid;
 
 pub async fn init_router(
-    mock_db: MockDatabase,
+    mock_db: MockTodoRepository,
     uri: &str,
     router: MethodRouter<SharedState>,
 ) -> Router {
-    let app_state = Arc::new(AppState {
-        db: Database::Mock(mock_db),
-        credentials: vec![("user".to_string(), "pass".to_string())],
-    });
-    Router::new().route(uri, router).with_state(app_state)
+    Router::new()
+        .route(uri, router)
+        .with_state(test_state(mock_db))
+}
+
+pub fn test_state(mock_db: MockTodoRepository) -> SharedState {
+    Arc::new(AppState {
+        db: Arc::new(mock_db),
+        credentials: test_credentials(),
+        tokens: test_tokens(),
+        rate_limiter: RateLimiter::new(RateLimits::off()),
+        idempotency_ttl: Duration::from_secs(60),
+        shutting_down: AtomicBool::new(false),
+    })
+}
+
+/// Stored todo in its first version, tests change the other fields with struct update syntax.
+pub fn db_todo(id: Uuid, owner: &str, text: &str) -> DbTodo {
+    DbTodo {
+        id,
+        text: text.to_string(),
+        completed: false,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        completed_at: None,
+        version: 1,
+        owner: owner.to_string(),
+        list_id: None,
+        tags: Vec::new(),
+        due_at: None,
+        priority: Default::default(),
+    }
+}
+
+/// Credentials of the editor `user` and of `admin`, both with password `pass` hashed with
+/// cheap parameters.
+pub fn test_credentials() -> Credentials {
+    let hash =
+        "$argon2id$v=19$m=8,t=1,p=1$c2FsdHNhbHRzYWx0$wH39oZXzTjsLQboJAaXtb53+uphmEb9evfHyY9E7eMs";
+    Credentials::parse(&format!("user:{} admin:{}:admin", hash, hash)).unwrap()
+}
+
+pub fn test_tokens() -> TokenKeys {
+    TokenKeys::new(b"test secret of at least 32 bytes", Duration::from_secs(60))
+}
+
+/// `Authorization` header with an access token of the user as editor.
+pub fn test_bearer(user: &str) -> String {
+    test_bearer_role(user, Role::Editor)
+}
+
+pub fn test_bearer_role(user: &str, role: Role) -> String {
+    format!("Bearer {}", test_tokens().issue(user, role).unwrap())
 }
 
+// the requests of the helpers below are authenticated as `user`, unless headers say otherwise
+
 pub async fn test_get(app: Router, uri: &str) -> Response<Body> {
     app.oneshot(
         Request::builder()
             .method("GET")
             .uri(uri)
+            .header("Authorization", test_bearer("user"))
             .body(Body::empty())
             .unwrap(),
     )
@@ -45,6 +107,7 @@ where
             .method("POST")
             .uri(uri)
             .header("content-type", "application/json")
+            .header("Authorization", test_bearer("user"))
             .body(Body::from(serde_json::to_string(&body).unwrap()))
             .unwrap(),
     )
@@ -57,6 +120,7 @@ pub async fn test_delete(app: Router, uri: &str) -> Response<Body> {
         Request::builder()
             .method("DELETE")
             .uri(uri)
+            .header("Authorization", test_bearer("user"))
             .body(Body::empty())
             .unwrap(),
     )
@@ -64,6 +128,33 @@ pub async fn test_delete(app: Router, uri: &str) -> Response<Body> {
     .unwrap()
 }
 
+pub async fn test_with_headers(
+    app: Router,
+    method: &str,
+    uri: &str,
+    headers: &[(&str, &str)],
+    body: Option<serde_json::Value>,
+) -> Response<Body> {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+    {
+        request = request.header("Authorization", test_bearer("user"));
+    }
+    let body = match body {
+        Some(body) => {
+            request = request.header("content-type", "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+    app.oneshot(request.body(body).unwrap()).await.unwrap()
+}
+
 pub async fn test_authenticated(
     app: Router,
     uri: &str,